@@ -1,292 +1,184 @@
-#import bevy_pbr::{
-    mesh_view_bindings::globals,
-    forward_io::VertexOutput,
+#import bevy_pbr::forward_io::VertexOutput
+#import bevy_pbr::mesh_view_bindings::view
+#import bevy_pbr::mesh_functions::get_world_from_local
+#import aurora::core::{AuroraSettings, AuroraPhases, SkyPoint, aurora, direction_point, above_horizon, GROUND_COLOR, fbm, oklab_to_linear_srgb, pcg, physical_emission, seed_hash}
+
+// Parameters of the mappings, mirrored by `AuroraMappingSettings` on the Rust side
+struct AuroraMappingSettings {
+    aspect: f32,
+    world_size: f32,
+    world_origin: vec2<f32>,
+    object_size: f32,
+    volume_steps: u32,
+    volume_distance: f32,
+    volume_altitude: f32,
+    volume_thickness: f32,
+    volume_size: f32,
 }
 
-// OKLab color space conversions for perceptually accurate color blending
-fn oklab_to_linear_srgb(c: vec3<f32>) -> vec3<f32> {
-    let L = c.x;
-    let a = c.y;
-    let b = c.z;
+@group(2) @binding(0) var<uniform> settings: AuroraSettings;
+@group(2) @binding(1) var<uniform> phases: AuroraPhases;
+@group(2) @binding(2) var<uniform> mapping: AuroraMappingSettings;
+
+// Direction from the camera to the fragment, the sky is seen through the surface along it
+fn projected_direction(in: VertexOutput) -> vec3<f32> {
+    return normalize(in.world_position.xyz - view.world_position);
+}
 
-    let l_ = L + 0.3963377774 * a + 0.2158037573 * b;
-    let m_ = L - 0.1055613458 * a - 0.0638541728 * b;
-    let s_ = L - 0.0894841775 * a - 1.2914855480 * b;
+// A flat sky `width` times as wide as it is high, spread around the middle of the pattern so
+// it keeps its proportions, with `v` going up
+fn flat_point(uv: vec2<f32>, width: f32) -> SkyPoint {
+    return SkyPoint(vec2<f32>((uv.x - 0.5) * width + 0.5, uv.y), uv.y);
+}
 
-    let l = l_ * l_ * l_;
-    let m = m_ * m_ * m_;
-    let s = s_ * s_ * s_;
+#ifdef AURORA_MAPPING_TRIPLANAR
+// How much the projection facing the normal wins over the others where they are blended
+const TRIPLANAR_SHARPNESS: f32 = 4.0;
 
-    return vec3<f32>(
-        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
-        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
-        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
-    );
+// Inverse of a 3x3 matrix: the cross products of its columns are the rows of its inverse,
+// scaled by the determinant
+fn inverse_3x3(m: mat3x3<f32>) -> mat3x3<f32> {
+    let rows = mat3x3<f32>(cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1]));
+    return transpose(rows) * (1.0 / dot(m[0], rows[0]));
 }
 
-// Hash function for random values
-fn hash(p: vec2<f32>) -> f32 {
-    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+// The aurora on a mesh without usable UVs. The mesh's own space is projected along its three
+// axes and the three skies are blended by the normal, so the pattern has no seam and sticks
+// to the mesh as it moves. Side faces show the aurora upright, top and bottom faces like
+// `WorldXz` does
+fn triplanar(in: VertexOutput) -> vec4<f32> {
+    let world_from_local = get_world_from_local(in.instance_index);
+    let linear = mat3x3<f32>(world_from_local[0].xyz, world_from_local[1].xyz, world_from_local[2].xyz);
+    let local = inverse_3x3(linear) * (in.world_position.xyz - world_from_local[3].xyz);
+    let p = local / mapping.object_size + 0.5;
+    // World normals are local ones through the inverse transpose, so this undoes it up to a scale
+    let normal = normalize(transpose(linear) * in.world_normal);
+    let weights = pow(abs(normal), vec3<f32>(TRIPLANAR_SHARPNESS));
+    let w = weights / (weights.x + weights.y + weights.z);
+    return aurora(SkyPoint(p.zy, p.y), settings, phases) * w.x
+        + aurora(SkyPoint(p.xz, p.z), settings, phases) * w.y
+        + aurora(SkyPoint(p.xy, p.y), settings, phases) * w.z;
 }
+#else ifdef AURORA_MAPPING_VOLUMETRIC
+// Light given off by the curtains per world unit a ray travels through them at full density
+const VOLUME_EMISSION: f32 = 0.08;
+// Most samples taken along a ray, whatever the settings ask for
+const VOLUME_MAX_STEPS: u32 = 256u;
 
-// 2D Noise function based on Perlin noise principles
-fn noise21(p: vec2<f32>) -> f32 {
-    let i = floor(p);
-    let f = fract(p);
-    
-    // Cubic Hermite interpolation for smoother blending
-    let u = f * f * (3.0 - 2.0 * f);
-    
-    // Four corners hash values
-    let a = hash(i + vec2<f32>(0.0, 0.0));
-    let b = hash(i + vec2<f32>(1.0, 0.0));
-    let c = hash(i + vec2<f32>(0.0, 1.0));
-    let d = hash(i + vec2<f32>(1.0, 1.0));
-    
-    // Bilinear interpolation
-    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+// How dense the curtains are at world position `p`, `h` being how far up through their layer
+// it is. The sheets are folded by the same large scale noise and wave as the flat aurora, and
+// lit along their length by the same flow
+fn curtain_density(p: vec3<f32>, h: f32) -> f32 {
+    let coord = (p.xz - mapping.world_origin) / mapping.volume_size + 0.5;
+    let noise_coord = vec2<f32>(coord.x * 2.0, coord.y * settings.y_stretch) + phases.drift_a.xy;
+    let large_noise = fbm(noise_coord, 3) * settings.waviness;
+
+    // Thin vertical sheets running along X, folded by the noise
+    let fold = sin(coord.y * 15.0 + phases.oscillators_a.x + large_noise * 5.0);
+    let sheet = pow(max(fold, 0.0), 8.0);
+    // Brighter and dimmer rays along each sheet
+    let flow = sin(coord.x * 10.0 + large_noise * 3.0 + phases.oscillators_a.y) * 0.5 + 0.5;
+    // A sharp lower edge and a long fade upwards, like real curtains
+    let profile = smoothstep(0.0, 0.05, h) * exp(-3.0 * h);
+    return sheet * mix(0.3, 1.0, flow) * profile * smoothstep(0.0, 0.4, large_noise + 0.1);
 }
 
-// Fractional Brownian Motion for layered noise
-fn fbm(p: vec2<f32>, octaves: i32) -> f32 {
-    var value = 0.0;
-    var amplitude = 0.5;
-    var frequency = 2.0;
-    
-    for (var i = 0; i < octaves; i = i + 1) {
-        value += amplitude * noise21(p * frequency);
-        amplitude *= 0.5;
-        frequency *= 2.0;
+// The curtains seen from the camera through the surface, ray marched through their layer
+// between `volume_altitude` and `volume_altitude + volume_thickness`, so they move with
+// parallax as the camera does
+fn volume(in: VertexOutput) -> vec4<f32> {
+    seed_hash = pcg(settings.seed);
+
+    let origin = view.world_position;
+    let direction = projected_direction(in);
+    let bottom = mapping.volume_altitude;
+    let thickness = max(mapping.volume_thickness, 1e-3);
+
+    // Where the ray enters and leaves the layer, if it ever does
+    var enter = 0.0;
+    var leave = mapping.volume_distance;
+    if abs(direction.y) > 1e-5 {
+        let t0 = (bottom - origin.y) / direction.y;
+        let t1 = (bottom + thickness - origin.y) / direction.y;
+        enter = max(min(t0, t1), 0.0);
+        leave = min(max(t0, t1), mapping.volume_distance);
+    } else if origin.y < bottom || origin.y > bottom + thickness {
+        leave = 0.0;
     }
-    
-    return value;
-}
 
-// Nebula effect for subtle space dust
-fn nebula(coord: vec2<f32>, time: f32) -> vec3<f32> {
-    // Very subtle shifting noise field
-    let nebula_noise = fbm(coord * 4.0 + vec2<f32>(time * 0.01, 0.0), 3) * 0.15;
-    
-    // Vary nebula color based on position
-    let hue = fbm(coord * 2.0 - vec2<f32>(time * 0.02, 0.0), 2);
-    
-    // Very subtle bluish/purplish dust
-    let nebula_color = mix(
-        vec3<f32>(0.02, 0.035, 0.05),  // Deep blue
-        vec3<f32>(0.04, 0.02, 0.06),   // Purple tint
-        hue
-    );
-    
-    return nebula_color * nebula_noise * smoothstep(0.4, 0.6, noise21(coord * 3.0 + vec2<f32>(time * 0.03, 0.0)));
-}
+    let steps = clamp(mapping.volume_steps, 1u, VOLUME_MAX_STEPS);
+    let step = max(leave - enter, 0.0) / f32(steps);
+    // Every pixel starts at a different fraction of a step, trading banding for fine grain
+    let jitter = fract(52.9829189 * fract(dot(in.position.xy, vec2<f32>(0.06711056, 0.00583715))));
 
+    // The palette of the flat aurora: its main layer low down, its second layer high up
+    let low_color = oklab_to_linear_srgb(mix(settings.green, settings.teal, sin(phases.oscillators_a.z) * 0.5 + 0.5));
+    let high_color = oklab_to_linear_srgb(mix(settings.red, settings.pink, sin(phases.oscillators_c.x) * 0.5 + 0.5));
+
+    var emission = vec3<f32>(0.0);
+    var density = 0.0;
+    for (var i = 0u; i < steps; i = i + 1u) {
+        let t = enter + (f32(i) + jitter) * step;
+        let p = origin + direction * t;
+        let h = clamp((p.y - bottom) / thickness, 0.0, 1.0);
+        // Fade out towards the far end instead of cutting off
+        let fade = 1.0 - smoothstep(0.7, 1.0, t / mapping.volume_distance);
+        let sample_density = curtain_density(p, h) * fade * step;
+        let palette_color = mix(low_color, high_color * settings.second_strength, smoothstep(0.3, 0.9, h));
+        let color = mix(palette_color, physical_emission(h), settings.physical_emission);
+        emission += color * sample_density;
+        density += sample_density;
+    }
+    emission *= VOLUME_EMISSION;
+    let intensity = clamp(density * VOLUME_EMISSION, 0.0, 1.0);
+
+    // Same night sky gradient as the flat aurora, fading into the ground below the horizon
+    let elevation = asin(clamp(direction.y, 0.0, 1.0)) / 1.5707963;
+    let night = mix(vec3<f32>(0.0, 0.01, 0.03), vec3<f32>(0.01, 0.03, 0.07), elevation * 0.7);
+    let ground = smoothstep(-0.1, 0.0, direction.y);
+    let color = mix(GROUND_COLOR, night, ground) + emission;
+    return vec4<f32>(color, intensity * settings.alpha_scale + settings.alpha_base);
+}
+#else
+// Where a fragment is in the sky, from the mesh UVs or from where it is in the world or on
+// the screen
+fn sky_point(in: VertexOutput) -> SkyPoint {
+#ifdef AURORA_MAPPING_DOME
+    // Dome UVs are azimuth and elevation, see `SkyDome` on the Rust side
+    let azimuth = in.uv.x * 6.2831853;
+    let altitude = in.uv.y * 1.5707963;
+    return direction_point(vec3<f32>(cos(altitude) * sin(azimuth), sin(altitude), cos(altitude) * cos(azimuth)));
+#else ifdef AURORA_MAPPING_WORLD_XZ
+    // One sky square of `world_size` around `world_origin`, shared by every mesh using it, so
+    // tiles line up. Laid out like the UVs of a `Plane3d`, `v` going towards +Z
+    let plane = (in.world_position.xz - mapping.world_origin) / mapping.world_size + 0.5;
+    return SkyPoint(plane, plane.y);
+#else ifdef AURORA_MAPPING_WORLD_PROJECTED
+    return direction_point(above_horizon(projected_direction(in)));
+#else ifdef AURORA_MAPPING_SCREEN
+    // Fills the viewport's height whatever the shape of the mesh, `y` going down on screen
+    let screen = (in.position.xy - view.viewport.xy) / view.viewport.zw;
+    return flat_point(vec2<f32>(screen.x, 1.0 - screen.y), view.viewport.z / max(view.viewport.w, 1.0));
+#else
+    return flat_point(in.uv, mapping.aspect);
+#endif
+}
+#endif
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Aurora-specific parameters
-    let time = globals.time * 0.2;
-    let coord = in.uv;
-    
-    // The variable we'll use for the final color
-    var result_color = vec3<f32>(0.0);
-    
-    // Aurora tends to appear in bands across the sky
-    let y_stretch = 3.0; // Stretch the effect vertically
-    let waviness = 0.8; // How wavy the aurora bands are
-    
-    // Create multiple layers of noise with different frequencies
-    let noise_coord = vec2<f32>(coord.x * 2.0, coord.y * y_stretch) + vec2<f32>(time * 0.1, time * 0.05);
-    let large_noise = fbm(noise_coord, 3) * waviness;
-    
-    // Create wave-like vertical displacement
-    let wave_effect = sin(coord.y * 15.0 + time + large_noise * 5.0) * 0.05;
-    let displaced_x = coord.x + wave_effect;
-    
-    // Create flow and movement
-    let flow = sin(displaced_x * 10.0 + large_noise * 3.0 + time * 0.7) * 0.5 + 0.5;
-    let height_mask = smoothstep(0.0, 0.7, 1.0 - abs(coord.y - 0.5) * 2.0); // Stronger in the middle
-    
-    // Aurora intensity varies with height and flow
-    let intensity = flow * height_mask * smoothstep(0.0, 0.4, large_noise + 0.1);
-    
-    // Distance from center affects color mixing
-    let dist_center = distance(coord, vec2<f32>(0.5, 0.5));
-    let dist_factor = smoothstep(0.0, 1.2, dist_center);
-    
-    // Layer of smaller, faster moving details
-    let small_noise = fbm(noise_coord * 4.0 + vec2<f32>(time * 0.2, 0.0), 2) * 0.4;
-    let detail_intensity = small_noise * intensity * 0.8;
-    
-    // Additional fine detail layer for more complexity
-    let fine_noise = fbm(noise_coord * 8.0 + vec2<f32>(time * 0.3, time * 0.15), 3) * 0.3;
-    let fine_detail = fine_noise * intensity * smoothstep(0.2, 0.8, large_noise) * 0.6;
-    
-    // Dynamic intensity layer - random surges in brightness on a different cadence
-    // Create a different pulsing pattern with irregular timing for variety
-    let surge_frequency = 0.03; // Different frequency from curtains for variety
-    let surge_phase = hash(floor(vec2<f32>(time * surge_frequency, 1.0))); // Different seed
-    let surge_timing = smoothstep(0.7, 0.9, sin(time * surge_frequency * 6.28 + surge_phase * 10.0));
-    
-    // Make surges more localized and subtle
-    let surge_x = coord.x * 3.0 + large_noise * 2.0;
-    let surge_y = coord.y * 4.0 + large_noise * 3.0;
-    
-    // Spatial patterns for surges
-    let surge_pattern1 = smoothstep(0.45, 0.65, sin(time * 0.3 + surge_x + surge_y));
-    let surge_pattern2 = smoothstep(0.55, 0.75, sin(time * 0.2 - surge_x + surge_y * 2.0));
-    let surge_pattern3 = smoothstep(0.65, 0.85, cos(time * 0.15 + surge_x * 2.0 - surge_y));
-    
-    // Make surges extremely subtle, almost imperceptible
-    // More selective timing with higher threshold
-    let subtle_surge_timing = smoothstep(0.8, 0.95, sin(time * surge_frequency * 6.28 + surge_phase * 10.0));
-    
-    // Drastically reduced intensity and only active during very brief moments
-    let intensity_surge = max(surge_pattern1, max(surge_pattern2, surge_pattern3)) 
-                        * intensity * 0.12 * subtle_surge_timing; // Reduced by ~3x
-    
-    // Time-varying parameters for color animation
-    let t1 = sin(time * 0.3) * 0.5 + 0.5;
-    let t2 = cos(time * 0.2) * 0.5 + 0.5;
-    let t3 = sin(time * 0.4 + dist_center) * 0.5 + 0.5;
-    
-    // Aurora borealis colors in OKLab space for better blending
-    // Vibrant greens and teals are common in auroras
-    let green = vec3<f32>(0.86644, -0.233887, 0.179498);  // Vibrant green
-    let teal = vec3<f32>(0.7, -0.1, 0.1);                 // Bluish-green
-    let blue = vec3<f32>(0.701674, 0.174566, -0.269156);  // Cold blue
-    let purple = vec3<f32>(0.7, 0.3, -0.1);               // Purplish hue
-    let red = vec3<f32>(0.75, 0.35, 0.15);               // Reddish aurora
-    let pink = vec3<f32>(0.78, 0.27, 0.05);              // Pinkish aurora
-    
-    // Final color is a complex mix based on multiple parameters
-    let color1 = mix(green, teal, t1);
-    let color2 = mix(blue, purple, t2);
-    // Add fine detail to the color mixing for more complexity
-    let mixed_color = mix(color1, color2, t3 * dist_factor + detail_intensity + fine_detail * 0.5);
-    
-    // Barely visible microdetail pattern
-    let micro_noise_coord = noise_coord * 12.0 + vec2<f32>(time * 0.4, time * -0.3); // Lower frequency
-    let micro_noise = fbm(micro_noise_coord, 2) * 0.07 * intensity; // Drastically reduced
-    let micro_detail = smoothstep(0.35, 0.65, sin(coord.y * 30.0 + large_noise * 10.0)) * // Lower frequency
-                      intensity * 0.08; // Drastically reduced
-    
-    // Apply intensity to color and convert back to linear RGB
-    // Include all detail layers and intensity surge in the final intensity
-    let aurora_lab_color = mixed_color * (intensity + detail_intensity * 0.7 + fine_detail * 0.6 + 
-                                         micro_noise + micro_detail + intensity_surge);
-    
-    // Create a specific surge color that's more vibrant
-    let surge_color = mix(green, vec3<f32>(0.9, -0.1, 0.2), 0.3); // Brighter, slightly more yellowish
-    let surge_contribution = surge_color * intensity_surge * 0.7;
-    
-    // Final color with surge
-    let aurora_with_surge = aurora_lab_color + surge_contribution;
-    let rgb_color = oklab_to_linear_srgb(aurora_with_surge);
-    
-    // Add a subtle glow effect
-    let glow = intensity * 0.4;
-    let glow_color = mix(vec3<f32>(0.05, 0.1, 0.2), rgb_color, intensity);
-    
-    // Add a second fine detail pattern with different orientation
-    let cross_detail = fbm(vec2<f32>(coord.y * 6.0, coord.x * 12.0) + vec2<f32>(time * 0.15, -time * 0.05), 2) * 0.2;
-    let cross_effect = cross_detail * smoothstep(0.0, 0.6, intensity) * 0.3;
-    
-    // Fine wisps in the aurora
-    let wisps = smoothstep(0.3, 0.7, sin(coord.y * 30.0 + large_noise * 10.0 + time * 0.2)) * intensity * 0.15;
-    
-    // Create a second, reddish aurora layer with offset
-    // Use a different offset and scale for the second aurora
-    let aurora2_offset = vec2<f32>(0.2, -0.1); // Offset for the second aurora
-    let aurora2_noise_coord = vec2<f32>(
-        coord.x * 1.5 + aurora2_offset.x,
-        coord.y * 2.5 + aurora2_offset.y
-    ) + vec2<f32>(time * 0.08, time * 0.02); // Different speed
-    
-    // Additional high-frequency detail for second aurora
-    let aurora2_detail_coord = aurora2_noise_coord * 3.0 + vec2<f32>(time * -0.2, time * 0.1);
-    let aurora2_detail = fbm(aurora2_detail_coord, 2) * 0.5;
-    
-    // Create wave patterns for second aurora
-    let aurora2_large_noise = fbm(aurora2_noise_coord, 3) * 0.7;
-    let aurora2_wave = sin(coord.y * 10.0 + time * 0.8 + aurora2_large_noise * 4.0) * 0.07;
-    let aurora2_displaced_x = coord.x + aurora2_wave;
-    
-    // Different flow pattern
-    let aurora2_flow = sin(aurora2_displaced_x * 8.0 + aurora2_large_noise * 2.0 + time * 0.5) * 0.5 + 0.5;
-    // Concentrated more toward the top
-    let aurora2_height_mask = smoothstep(0.0, 0.6, 1.0 - abs(coord.y - 0.6) * 2.0);
-    let aurora2_intensity = aurora2_flow * aurora2_height_mask * smoothstep(0.0, 0.4, aurora2_large_noise);
-    
-    // Reddish color mix for second aurora
-    let t4 = sin(time * 0.25) * 0.5 + 0.5;
-    let aurora2_color_mix = mix(red, pink, t4);
-    
-    // Convert to RGB with reduced intensity compared to main aurora
-    // Incorporate the high-frequency detail into the aurora
-    let aurora2_lab_color = aurora2_color_mix * (aurora2_intensity * 0.7 + aurora2_detail * aurora2_intensity * 0.4);
-    let aurora2_rgb = oklab_to_linear_srgb(aurora2_lab_color);
-    
-    // Add some fine wispy structures to the second aurora
-    let aurora2_wisps = smoothstep(0.4, 0.6, sin(coord.y * 40.0 + aurora2_large_noise * 15.0 + time * 0.3)) * 
-                       aurora2_intensity * 0.2;
-    let aurora2_wisp_color = oklab_to_linear_srgb(mix(red, pink, 0.3) * aurora2_wisps);
-    
-    // Add vertical curtain-like structures with fine detail that appear randomly
-    // Create a pulsing pattern with approximately 2-3 second intervals
-    // At 60fps, we need roughly 120-180 frames per cycle, so a frequency of about 0.03-0.05 Hz
-    let pulse_frequency = 0.04; // Adjust for cycle time (lower = longer cycle)
-    let pulse_phase = hash(floor(vec2<f32>(time * pulse_frequency, 0.0))); // Random phase per interval
-    let pulse_intensity = smoothstep(0.75, 0.95, sin(time * pulse_frequency * 6.28 + pulse_phase * 6.28));
-    
-    // Make curtains even more subtle and rare
-    let curtain_x = coord.x * 15.0 + large_noise * 5.0; // Lower frequency
-    // Much rarer pulses by using higher threshold
-    let pulse_visibility = smoothstep(0.85, 0.95, sin(time * pulse_frequency * 6.28 + pulse_phase * 6.28));
-    let curtain_pattern = smoothstep(0.45, 0.65, sin(curtain_x + time * 0.7)) * // Wider smoothstep
-                        smoothstep(0.3, 0.7, intensity) * 0.03 * // Drastically reduced intensity
-                        pulse_visibility; // Extremely selective timing
-    
-    // Drastically reduce the ray pattern to near-imperceptible levels
-    // Fine rays emanating from curtains with extremely subtle presence
-    let rays_pattern = smoothstep(0.40, 0.70, // Very wide smoothstep for soft edges
-                                 sin(coord.y * 40.0 + large_noise * 8.0 + time * 0.1) * // Lower frequency
-                                 smoothstep(0.45, 0.65, sin(curtain_x + time * 0.7))) // Wider smoothstep
-                     * intensity * 0.04 * // Drastically reduced intensity
-                     pulse_visibility; // Only during the rare pulse moments
-    
-    let curtain_color = oklab_to_linear_srgb(mix(green, teal, 0.5) * curtain_pattern);
-    let rays_color = oklab_to_linear_srgb(mix(green, blue, 0.4) * rays_pattern);
-    
-    // Combine all effects for final aurora color
-    let aurora_color = rgb_color + glow_color * glow + 
-                      vec3<f32>(0.1, 0.15, 0.2) * cross_effect + 
-                      rgb_color * wisps +
-                      aurora2_rgb + // Add the second aurora layer
-                      aurora2_wisp_color + // Additional wisps to second aurora
-                      curtain_color + // Add curtain structures
-                      rays_color; // Add vertical rays
-    
-    // Generate celestial elements
-    let nebula_color = nebula(coord, time);
-    
-    // Create a subtle vignette effect
-    let vignette = smoothstep(1.2, 0.5, length(coord - vec2<f32>(0.5)));
-    
-    // Background gradient for night sky
-    let bg_gradient = mix(
-        vec3<f32>(0.0, 0.01, 0.03),  // Bottom - darker
-        vec3<f32>(0.01, 0.03, 0.07), // Top - slightly lighter
-        coord.y * 0.7
-    ) * vignette;
-    
-    // Create the night sky background first
-    let sky_color = bg_gradient + nebula_color;
-    
-    // Aurora overlay 
-    let aurora_blend_factor = clamp(intensity * 0.7, 0.0, 0.8); 
-    
-    // Blend aurora over the sky
-    let with_aurora = mix(sky_color, aurora_color, aurora_blend_factor);
-    
-    return vec4<f32>(with_aurora, intensity * 0.9 + 0.1);
+#ifdef AURORA_MAPPING_TRIPLANAR
+    return triplanar(in);
+#else ifdef AURORA_MAPPING_VOLUMETRIC
+    return volume(in);
+#else
+    let color = aurora(sky_point(in), settings, phases);
+#ifdef AURORA_MAPPING_WORLD_PROJECTED
+    // Below the horizon the sky fades into the ground, like in `sky()`
+    let ground = smoothstep(-0.1, 0.0, projected_direction(in).y);
+    return vec4<f32>(mix(GROUND_COLOR, color.rgb, ground), color.a);
+#else
+    return color;
+#endif
+#endif
 }
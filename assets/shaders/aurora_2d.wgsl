@@ -0,0 +1,12 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import aurora::core::{AuroraSettings, AuroraPhases, SkyPoint, aurora}
+
+@group(2) @binding(0) var<uniform> settings: AuroraSettings;
+@group(2) @binding(1) var<uniform> phases: AuroraPhases;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 2D meshes such as `Rectangle` have `v` going down, the sky goes up
+    let uv = vec2<f32>(in.uv.x, 1.0 - in.uv.y);
+    return aurora(SkyPoint(uv, uv.y), settings, phases);
+}
@@ -0,0 +1,23 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import aurora::core::{AuroraSettings, AuroraPhases, sky}
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(0) @binding(1) var<uniform> settings: AuroraSettings;
+@group(0) @binding(2) var<uniform> phases: AuroraPhases;
+
+// World space point at the depth `z` of the clip space under this pixel
+fn world_at(ndc: vec2<f32>, z: f32) -> vec3<f32> {
+    let world = view.world_from_clip * vec4<f32>(ndc, z, 1.0);
+    return world.xyz / world.w;
+}
+
+// Drawn behind the opaque geometry, where the depth buffer was left cleared
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let ndc = vec2<f32>(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0);
+    // Two points along the ray of this pixel, as the far plane is at infinity with Bevy's
+    // reversed depth. Works for perspective and orthographic projections alike
+    let direction = normalize(world_at(ndc, 0.5) - world_at(ndc, 1.0));
+    return vec4<f32>(sky(direction, settings, phases), 1.0);
+}
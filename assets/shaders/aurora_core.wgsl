@@ -0,0 +1,487 @@
+#define_import_path aurora::core
+
+// The look of the aurora, shared by every shader drawing it. Mirrored by the `cpu` module on
+// the Rust side, which must be kept in sync.
+
+// Per-material knobs, mirrored by `AuroraSettings` on the Rust side
+struct AuroraSettings {
+    speed: f32,
+    // Uploaded because the Rust struct derives its layout, but only read there, to compute the
+    // phases. Kept so the fields after it stay at the same offsets.
+    loop_period: f32,
+    y_stretch: f32,
+    waviness: f32,
+    surge_frequency: f32,
+    pulse_frequency: f32,
+    blend_strength: f32,
+    blend_cap: f32,
+    alpha_base: f32,
+    alpha_scale: f32,
+    seed: u32,
+
+    green: vec3<f32>,
+    teal: vec3<f32>,
+    blue: vec3<f32>,
+    purple: vec3<f32>,
+    red: vec3<f32>,
+    pink: vec3<f32>,
+
+    drift: vec2<f32>,
+    second_drift: vec2<f32>,
+    height: f32,
+    height_falloff: f32,
+    second_height: f32,
+    second_height_falloff: f32,
+
+    glow_strength: f32,
+    wisp_strength: f32,
+    second_strength: f32,
+    surge_strength: f32,
+    curtain_strength: f32,
+    ray_strength: f32,
+    nebula_strength: f32,
+    physical_emission: f32,
+}
+
+// Time-dependent terms, mirrored by `AuroraPhases` on the Rust side. They are computed there
+// in double precision and kept small, so the animation stays smooth however long it runs:
+// oscillator phases in [0, 2π), noise offsets modulo NOISE_PERIOD and pulse cycle indices.
+struct AuroraPhases {
+    // wave, flow and curtains, surge pattern 1 and t1, surge pattern 2 and t2
+    oscillators_a: vec4<f32>,
+    // surge pattern 3, t3, second layer wave, second layer flow
+    oscillators_b: vec4<f32>,
+    // t4, rays
+    oscillators_c: vec4<f32>,
+    // surge cycle index and progress, pulse cycle index and progress
+    cycles: vec4<f32>,
+    // main layer offset, fine detail offset
+    drift_a: vec4<f32>,
+    // micro detail offset, cross detail offset
+    drift_b: vec4<f32>,
+    // second layer offset, second layer detail offset
+    drift_c: vec4<f32>,
+    // small detail x offset, nebula x offsets
+    drift_d: vec4<f32>,
+}
+
+
+// Period of the noise lattice, all noise offsets are wrapped to it
+const NOISE_PERIOD: u32 = 256u;
+
+// Hashed seed, set once at the start of `aurora()`
+var<private> seed_hash: u32;
+
+// OKLab color space conversions for perceptually accurate color blending
+fn oklab_to_linear_srgb(c: vec3<f32>) -> vec3<f32> {
+    let L = c.x;
+    let a = c.y;
+    let b = c.z;
+
+    let l_ = L + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = L - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = L - 0.0894841775 * a - 1.2914855480 * b;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    return vec3<f32>(
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    );
+}
+
+// Spectral lines of the aurora, in nanometers: the green and red lines of atomic oxygen and
+// the blue line of ionized molecular nitrogen
+const OXYGEN_GREEN_LINE: f32 = 557.7;
+const OXYGEN_RED_LINE: f32 = 630.0;
+const NITROGEN_BLUE_LINE: f32 = 427.8;
+
+// One lobe of the fit below: a Gaussian with a different width on each side of its peak
+fn cie_lobe(wavelength: f32, peak: f32, width_below: f32, width_above: f32) -> f32 {
+    let width = select(width_above, width_below, wavelength < peak);
+    let t = (wavelength - peak) / width;
+    return exp(-0.5 * t * t);
+}
+
+// CIE 1931 2° color matching functions at a wavelength in nanometers, from the multi-lobe fit
+// of "Simple Analytic Approximations to the CIE XYZ Color Matching Functions" (Wyman, Sloan &
+// Shirley, 2013)
+fn cie_xyz(wavelength: f32) -> vec3<f32> {
+    let x = 1.056 * cie_lobe(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * cie_lobe(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * cie_lobe(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * cie_lobe(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * cie_lobe(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * cie_lobe(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * cie_lobe(wavelength, 459.0, 26.0, 13.8);
+    return vec3<f32>(x, y, z);
+}
+
+// CIE XYZ to linear sRGB, D65 white point
+fn xyz_to_linear_srgb(xyz: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    );
+}
+
+// Linear sRGB color of a spectral line, at its chromaticity. Pure spectral colors lie outside
+// of sRGB, the negative components are clipped
+fn line_color(wavelength: f32) -> vec3<f32> {
+    let xyz = cie_xyz(wavelength);
+    return max(xyz_to_linear_srgb(xyz / (xyz.x + xyz.y + xyz.z)), vec3<f32>(0.0));
+}
+
+// Light given off by the aurora at `altitude` through it, from 0 at its lower edge to 1 at its
+// top, in linear RGB: a thin blue fringe of nitrogen at the bottom, the bright green oxygen line
+// above it, giving way to the fainter red one high up, and sunlit nitrogen tinting the very top
+fn physical_emission(altitude: f32) -> vec3<f32> {
+    let green = smoothstep(0.1, 0.3, altitude) * exp(-4.0 * max(altitude - 0.4, 0.0));
+    let red = 0.35 * smoothstep(0.5, 0.9, altitude);
+    let fringe = (altitude - 0.15) / 0.08;
+    let blue = 0.6 * exp(-fringe * fringe) + 0.25 * smoothstep(0.75, 1.0, altitude);
+    return line_color(OXYGEN_GREEN_LINE) * green
+        + line_color(OXYGEN_RED_LINE) * red
+        + line_color(NITROGEN_BLUE_LINE) * blue;
+}
+
+// PCG integer hash, see "Hash Functions for GPU Rendering" (Jarzynski & Olano, 2020)
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+// Hash function for random values in [0, 1) at integer lattice points
+fn hash(p: vec2<f32>) -> f32 {
+    let cell = bitcast<vec2<u32>>(vec2<i32>(floor(p))) & vec2<u32>(NOISE_PERIOD - 1u);
+    let h = pcg(cell.x ^ pcg(cell.y ^ seed_hash));
+    // Keep 24 bits so the conversion to f32 is exact
+    return f32(h >> 8u) / 16777216.0;
+}
+
+// 2D Noise function based on Perlin noise principles
+fn noise21(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    
+    // Cubic Hermite interpolation for smoother blending
+    let u = f * f * (3.0 - 2.0 * f);
+    
+    // Four corners hash values
+    let a = hash(i + vec2<f32>(0.0, 0.0));
+    let b = hash(i + vec2<f32>(1.0, 0.0));
+    let c = hash(i + vec2<f32>(0.0, 1.0));
+    let d = hash(i + vec2<f32>(1.0, 1.0));
+    
+    // Bilinear interpolation
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+// Fractional Brownian Motion for layered noise
+fn fbm(p: vec2<f32>, octaves: i32) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var frequency = 2.0;
+    
+    for (var i = 0; i < octaves; i = i + 1) {
+        value += amplitude * noise21(p * frequency);
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    
+    return value;
+}
+
+// Nebula effect for subtle space dust
+fn nebula(coord: vec2<f32>, offsets: vec3<f32>, strength: f32) -> vec3<f32> {
+    // Very subtle shifting noise field
+    let nebula_noise = fbm(coord * 4.0 + vec2<f32>(offsets.x, 0.0), 3) * strength;
+    
+    // Vary nebula color based on position
+    let hue = fbm(coord * 2.0 + vec2<f32>(offsets.y, 0.0), 2);
+    
+    // Very subtle bluish/purplish dust
+    let nebula_color = mix(
+        vec3<f32>(0.02, 0.035, 0.05),  // Deep blue
+        vec3<f32>(0.04, 0.02, 0.06),   // Purple tint
+        hue
+    );
+    
+    return nebula_color * nebula_noise * smoothstep(0.4, 0.6, noise21(coord * 3.0 + vec2<f32>(offsets.z, 0.0)));
+}
+
+// Where a point is in the sky: `coord` drives the aurora patterns, `elevation` places the
+// layers and the background gradient, from 0 at the bottom or horizon to 1 at the top or zenith
+struct SkyPoint {
+    coord: vec2<f32>,
+    elevation: f32,
+}
+
+// How far below the viewer the dome's projection plane starts, keeps the horizon at a finite
+// distance instead of stretching the patterns to infinity
+const DOME_HORIZON_OFFSET: f32 = 0.3;
+// Pattern units per unit of the dome's projection plane
+const DOME_SCALE: f32 = 0.5;
+
+// Below the horizon, where the sky fades out
+const GROUND_COLOR: vec3<f32> = vec3<f32>(0.002, 0.004, 0.008);
+
+// Maps a unit direction above the horizon to the sky, by projecting it onto a flat layer above
+// the viewer like a real aurora seen from the ground: continuous all around, with no seam
+fn direction_point(direction: vec3<f32>) -> SkyPoint {
+    let plane = direction.xz / (direction.y + DOME_HORIZON_OFFSET);
+    let elevation = asin(clamp(direction.y, 0.0, 1.0)) / 1.5707963;
+    return SkyPoint(plane * DOME_SCALE + 0.5, elevation);
+}
+
+// Direction a cube texture is sampled with to land on texel `st` of `face`, with `st` in [-1, 1]
+// going right and down across the face
+fn cube_direction(face: u32, st: vec2<f32>) -> vec3<f32> {
+    switch face {
+        case 0u: { return vec3<f32>(1.0, -st.y, -st.x); }
+        case 1u: { return vec3<f32>(-1.0, -st.y, st.x); }
+        case 2u: { return vec3<f32>(st.x, 1.0, st.y); }
+        case 3u: { return vec3<f32>(st.x, -1.0, -st.y); }
+        case 4u: { return vec3<f32>(st.x, -st.y, 1.0); }
+        default: { return vec3<f32>(-st.x, -st.y, -1.0); }
+    }
+}
+
+// Unit direction through the center of texel `id.xy` of face `id.z` of a cube texture of `size`
+fn cube_texel_direction(id: vec3<u32>, size: vec2<u32>) -> vec3<f32> {
+    let st = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(size) * 2.0 - 1.0;
+    return normalize(cube_direction(id.z, st));
+}
+
+// A unit direction flattened onto the horizon if it points below it
+fn above_horizon(direction: vec3<f32>) -> vec3<f32> {
+    // Straight down has no horizontal part, nudge it so the normalization stays defined
+    return normalize(vec3<f32>(direction.x, max(direction.y, 0.0) + 1e-6, direction.z));
+}
+
+// The whole sky seen in a world space direction, fading into the ground below the horizon
+fn sky(direction: vec3<f32>, settings: AuroraSettings, phases: AuroraPhases) -> vec3<f32> {
+    let color = aurora(direction_point(above_horizon(direction)), settings, phases).rgb;
+    return mix(GROUND_COLOR, color, smoothstep(-0.1, 0.0, direction.y));
+}
+
+// The aurora over the night sky at a point in the sky, in linear RGB with alpha
+fn aurora(point: SkyPoint, settings: AuroraSettings, phases: AuroraPhases) -> vec4<f32> {
+    seed_hash = pcg(settings.seed);
+
+    let coord = point.coord;
+    let elevation = point.elevation;
+    
+    // The variable we'll use for the final color
+    var result_color = vec3<f32>(0.0);
+    
+    // Aurora tends to appear in bands across the sky
+    let y_stretch = settings.y_stretch; // Stretch the effect vertically
+    let waviness = settings.waviness; // How wavy the aurora bands are
+    
+    // Create multiple layers of noise with different frequencies
+    let noise_coord = vec2<f32>(coord.x * 2.0, coord.y * y_stretch) + phases.drift_a.xy;
+    let large_noise = fbm(noise_coord, 3) * waviness;
+    
+    // Create wave-like vertical displacement
+    let wave_effect = sin(coord.y * 15.0 + phases.oscillators_a.x + large_noise * 5.0) * 0.05;
+    let displaced_x = coord.x + wave_effect;
+    
+    // Create flow and movement
+    let flow = sin(displaced_x * 10.0 + large_noise * 3.0 + phases.oscillators_a.y) * 0.5 + 0.5;
+    let height_mask = smoothstep(0.0, settings.height_falloff, 1.0 - abs(elevation - settings.height) * 2.0); // Stronger in the middle
+    
+    // Aurora intensity varies with height and flow
+    let intensity = flow * height_mask * smoothstep(0.0, 0.4, large_noise + 0.1);
+    
+    // Distance from center affects color mixing
+    let dist_center = distance(coord, vec2<f32>(0.5, 0.5));
+    let dist_factor = smoothstep(0.0, 1.2, dist_center);
+    
+    // Layer of smaller, faster moving details
+    let small_noise = fbm(noise_coord * 4.0 + vec2<f32>(phases.drift_d.x, 0.0), 2) * 0.4;
+    let detail_intensity = small_noise * intensity * 0.8;
+    
+    // Additional fine detail layer for more complexity
+    let fine_noise = fbm(noise_coord * 8.0 + phases.drift_a.zw, 3) * 0.3;
+    let fine_detail = fine_noise * intensity * smoothstep(0.2, 0.8, large_noise) * 0.6;
+    
+    // Dynamic intensity layer - random surges in brightness on a different cadence
+    // Create a different pulsing pattern with irregular timing for variety
+    let surge_phase = hash(vec2<f32>(phases.cycles.x, 1.0)); // Different seed
+    let surge_timing = smoothstep(0.7, 0.9, sin(phases.cycles.y * 6.28 + surge_phase * 10.0));
+    
+    // Make surges more localized and subtle
+    let surge_x = coord.x * 3.0 + large_noise * 2.0;
+    let surge_y = coord.y * 4.0 + large_noise * 3.0;
+    
+    // Spatial patterns for surges
+    let surge_pattern1 = smoothstep(0.45, 0.65, sin(phases.oscillators_a.z + surge_x + surge_y));
+    let surge_pattern2 = smoothstep(0.55, 0.75, sin(phases.oscillators_a.w - surge_x + surge_y * 2.0));
+    let surge_pattern3 = smoothstep(0.65, 0.85, cos(phases.oscillators_b.x + surge_x * 2.0 - surge_y));
+    
+    // Make surges extremely subtle, almost imperceptible
+    // More selective timing with higher threshold
+    let subtle_surge_timing = smoothstep(0.8, 0.95, sin(phases.cycles.y * 6.28 + surge_phase * 10.0));
+    
+    // Drastically reduced intensity and only active during very brief moments
+    let intensity_surge = max(surge_pattern1, max(surge_pattern2, surge_pattern3)) 
+                        * intensity * settings.surge_strength * subtle_surge_timing; // Reduced by ~3x
+    
+    // Time-varying parameters for color animation
+    let t1 = sin(phases.oscillators_a.z) * 0.5 + 0.5;
+    let t2 = cos(phases.oscillators_a.w) * 0.5 + 0.5;
+    let t3 = sin(phases.oscillators_b.y + dist_center) * 0.5 + 0.5;
+    
+    // Aurora borealis colors in OKLab space for better blending
+    // Vibrant greens and teals are common in auroras
+    let green = settings.green;   // Vibrant green
+    let teal = settings.teal;     // Bluish-green
+    let blue = settings.blue;     // Cold blue
+    let purple = settings.purple; // Purplish hue
+    let red = settings.red;       // Reddish aurora
+    let pink = settings.pink;     // Pinkish aurora
+    
+    // Final color is a complex mix based on multiple parameters
+    let color1 = mix(green, teal, t1);
+    let color2 = mix(blue, purple, t2);
+    // Add fine detail to the color mixing for more complexity
+    let mixed_color = mix(color1, color2, t3 * dist_factor + detail_intensity + fine_detail * 0.5);
+    
+    // Barely visible microdetail pattern
+    let micro_noise_coord = noise_coord * 12.0 + phases.drift_b.xy; // Lower frequency
+    let micro_noise = fbm(micro_noise_coord, 2) * 0.07 * intensity; // Drastically reduced
+    let micro_detail = smoothstep(0.35, 0.65, sin(coord.y * 30.0 + large_noise * 10.0)) * // Lower frequency
+                      intensity * 0.08; // Drastically reduced
+    
+    // Apply intensity to color and convert back to linear RGB
+    // Include all detail layers and intensity surge in the final intensity
+    let main_intensity = intensity + detail_intensity * 0.7 + fine_detail * 0.6 +
+                         micro_noise + micro_detail + intensity_surge;
+    let aurora_lab_color = mixed_color * main_intensity;
+    
+    // Create a specific surge color that's more vibrant
+    let surge_color = mix(green, vec3<f32>(0.9, -0.1, 0.2), 0.3); // Brighter, slightly more yellowish
+    let surge_contribution = surge_color * intensity_surge * 0.7;
+    
+    // Final color with surge
+    let aurora_with_surge = aurora_lab_color + surge_contribution;
+
+    // The physical model colors the main layer by the altitude through it instead
+    let emission = physical_emission(clamp(elevation - settings.height + 0.5, 0.0, 1.0));
+    let rgb_color = mix(oklab_to_linear_srgb(aurora_with_surge), emission * (main_intensity + intensity_surge * 0.7), settings.physical_emission);
+    
+    // Add a subtle glow effect
+    let glow = intensity * settings.glow_strength;
+    let glow_color = mix(vec3<f32>(0.05, 0.1, 0.2), rgb_color, intensity);
+    
+    // Add a second fine detail pattern with different orientation
+    let cross_detail = fbm(vec2<f32>(coord.y * 6.0, coord.x * 12.0) + phases.drift_b.zw, 2) * 0.2;
+    let cross_effect = cross_detail * smoothstep(0.0, 0.6, intensity) * 0.3;
+    
+    // Fine wisps in the aurora
+    let wisps = smoothstep(0.3, 0.7, sin(coord.y * 30.0 + large_noise * 10.0 + phases.oscillators_a.w)) * intensity * settings.wisp_strength;
+    
+    // Create a second, reddish aurora layer with offset
+    // Use a different offset and scale for the second aurora
+    let aurora2_offset = vec2<f32>(0.2, -0.1); // Offset for the second aurora
+    let aurora2_noise_coord = vec2<f32>(
+        coord.x * 1.5 + aurora2_offset.x,
+        coord.y * 2.5 + aurora2_offset.y
+    ) + phases.drift_c.xy; // Different speed
+    
+    // Additional high-frequency detail for second aurora
+    let aurora2_detail_coord = aurora2_noise_coord * 3.0 + phases.drift_c.zw;
+    let aurora2_detail = fbm(aurora2_detail_coord, 2) * 0.5;
+    
+    // Create wave patterns for second aurora
+    let aurora2_large_noise = fbm(aurora2_noise_coord, 3) * 0.7;
+    let aurora2_wave = sin(coord.y * 10.0 + phases.oscillators_b.z + aurora2_large_noise * 4.0) * 0.07;
+    let aurora2_displaced_x = coord.x + aurora2_wave;
+    
+    // Different flow pattern
+    let aurora2_flow = sin(aurora2_displaced_x * 8.0 + aurora2_large_noise * 2.0 + phases.oscillators_b.w) * 0.5 + 0.5;
+    // Concentrated more toward the top
+    let aurora2_height_mask = smoothstep(0.0, settings.second_height_falloff, 1.0 - abs(elevation - settings.second_height) * 2.0);
+    let aurora2_intensity = aurora2_flow * aurora2_height_mask * smoothstep(0.0, 0.4, aurora2_large_noise);
+    
+    // Reddish color mix for second aurora
+    let t4 = sin(phases.oscillators_c.x) * 0.5 + 0.5;
+    let aurora2_color_mix = mix(red, pink, t4);
+    
+    // Convert to RGB with reduced intensity compared to main aurora
+    // Incorporate the high-frequency detail into the aurora
+    let aurora2_strength = aurora2_intensity * settings.second_strength + aurora2_detail * aurora2_intensity * 0.4;
+    let aurora2_lab_color = aurora2_color_mix * aurora2_strength;
+    // The second layer is colored by its own altitude, its middle high up where the red line dominates
+    let aurora2_emission = physical_emission(clamp(elevation - settings.second_height + 0.9, 0.0, 1.0));
+    let aurora2_rgb = mix(oklab_to_linear_srgb(aurora2_lab_color), aurora2_emission * aurora2_strength, settings.physical_emission);
+    
+    // Add some fine wispy structures to the second aurora
+    let aurora2_wisps = smoothstep(0.4, 0.6, sin(coord.y * 40.0 + aurora2_large_noise * 15.0 + phases.oscillators_a.z)) * 
+                       aurora2_intensity * 0.2;
+    let aurora2_wisp_color = mix(oklab_to_linear_srgb(mix(red, pink, 0.3) * aurora2_wisps), aurora2_emission * aurora2_wisps, settings.physical_emission);
+    
+    // Add vertical curtain-like structures with fine detail that appear randomly
+    // Create a pulsing pattern with approximately 2-3 second intervals
+    // At 60fps, we need roughly 120-180 frames per cycle, so a frequency of about 0.03-0.05 Hz
+    let pulse_phase = hash(vec2<f32>(phases.cycles.z, 0.0)); // Random phase per interval
+    let pulse_intensity = smoothstep(0.75, 0.95, sin(phases.cycles.w * 6.28 + pulse_phase * 6.28));
+    
+    // Make curtains even more subtle and rare
+    let curtain_x = coord.x * 15.0 + large_noise * 5.0; // Lower frequency
+    // Much rarer pulses by using higher threshold
+    let pulse_visibility = smoothstep(0.85, 0.95, sin(phases.cycles.w * 6.28 + pulse_phase * 6.28));
+    let curtain_pattern = smoothstep(0.45, 0.65, sin(curtain_x + phases.oscillators_a.y)) * // Wider smoothstep
+                        smoothstep(0.3, 0.7, intensity) * settings.curtain_strength * // Drastically reduced intensity
+                        pulse_visibility; // Extremely selective timing
+    
+    // Drastically reduce the ray pattern to near-imperceptible levels
+    // Fine rays emanating from curtains with extremely subtle presence
+    let rays_pattern = smoothstep(0.40, 0.70, // Very wide smoothstep for soft edges
+                                 sin(coord.y * 40.0 + large_noise * 8.0 + phases.oscillators_c.y) * // Lower frequency
+                                 smoothstep(0.45, 0.65, sin(curtain_x + phases.oscillators_a.y))) // Wider smoothstep
+                     * intensity * settings.ray_strength * // Drastically reduced intensity
+                     pulse_visibility; // Only during the rare pulse moments
+    
+    let curtain_color = mix(oklab_to_linear_srgb(mix(green, teal, 0.5) * curtain_pattern), emission * curtain_pattern, settings.physical_emission);
+    let rays_color = mix(oklab_to_linear_srgb(mix(green, blue, 0.4) * rays_pattern), emission * rays_pattern, settings.physical_emission);
+    
+    // Combine all effects for final aurora color
+    let aurora_color = rgb_color + glow_color * glow + 
+                      vec3<f32>(0.1, 0.15, 0.2) * cross_effect + 
+                      rgb_color * wisps +
+                      aurora2_rgb + // Add the second aurora layer
+                      aurora2_wisp_color + // Additional wisps to second aurora
+                      curtain_color + // Add curtain structures
+                      rays_color; // Add vertical rays
+    
+    // Generate celestial elements
+    let nebula_color = nebula(coord, phases.drift_d.yzw, settings.nebula_strength);
+    
+    // Create a subtle vignette effect
+    let vignette = smoothstep(1.2, 0.5, length(coord - vec2<f32>(0.5)));
+    
+    // Background gradient for night sky
+    let bg_gradient = mix(
+        vec3<f32>(0.0, 0.01, 0.03),  // Bottom - darker
+        vec3<f32>(0.01, 0.03, 0.07), // Top - slightly lighter
+        elevation * 0.7
+    ) * vignette;
+    
+    // Create the night sky background first
+    let sky_color = bg_gradient + nebula_color;
+    
+    // Aurora overlay 
+    let aurora_blend_factor = clamp(intensity * settings.blend_strength, 0.0, settings.blend_cap);
+    
+    // Blend aurora over the sky
+    let with_aurora = mix(sky_color, aurora_color, aurora_blend_factor);
+    
+    return vec4<f32>(with_aurora, intensity * settings.alpha_scale + settings.alpha_base);
+}
@@ -0,0 +1,84 @@
+#import bevy_pbr::mesh_functions::get_world_from_local
+#import bevy_pbr::mesh_view_bindings::view
+#import aurora::core::{AuroraSettings, AuroraPhases, fbm, oklab_to_linear_srgb, pcg, physical_emission, seed_hash}
+
+// Mirrored by `CurtainFolds` on the Rust side
+struct CurtainFolds {
+    amplitude: f32,
+    size: f32,
+}
+
+@group(2) @binding(0) var<uniform> settings: AuroraSettings;
+@group(2) @binding(1) var<uniform> phases: AuroraPhases;
+@group(2) @binding(2) var<uniform> folds: CurtainFolds;
+
+// Only what the ribbon mesh has, see `ribbon_mesh` on the Rust side
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+}
+
+struct CurtainVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Along the ribbon from 0 to 1, and up it from 0 at the bottom edge to 1 at the top
+    @location(0) uv: vec2<f32>,
+    // World X and Z before folding, in noise units
+    @location(1) coord: vec2<f32>,
+    @location(2) large_noise: f32,
+}
+
+// Every ribbon is a flat sheet along its X axis. The vertices are pushed sideways by the large
+// scale noise of the flat aurora, sampled where they are in the world, so each ribbon folds
+// differently and the folds drift and ripple with the same phases. They are pushed in the
+// ribbon's own space, so its bounds hold the folds whatever the transform of its parent
+@vertex
+fn vertex(vertex: Vertex) -> CurtainVertexOutput {
+    seed_hash = pcg(settings.seed);
+
+    let world_from_local = get_world_from_local(vertex.instance_index);
+    let world = (world_from_local * vec4<f32>(vertex.position, 1.0)).xyz;
+    // A zero size would fold the ribbons to NaN
+    let coord = world.xz / max(folds.size, 1e-3);
+
+    // Same noise as `large_noise` and same wave as `wave_effect` in `aurora()`
+    let noise_coord = vec2<f32>(coord.x * 2.0, coord.y * settings.y_stretch) + phases.drift_a.xy;
+    let large_noise = fbm(noise_coord, 3) * settings.waviness;
+    let wave = sin(coord.x * 15.0 + phases.oscillators_a.x + large_noise * 5.0);
+
+    // Centered on the sheet, the top swaying a bit more than the bottom
+    let fold = (large_noise - 0.5 * settings.waviness + wave * 0.1) * folds.amplitude;
+    let local = vertex.position + vec3<f32>(0.0, 0.0, fold * (1.0 + vertex.uv.y * 0.3));
+    let folded = (world_from_local * vec4<f32>(local, 1.0)).xyz;
+
+    var out: CurtainVertexOutput;
+    out.position = view.clip_from_world * vec4<f32>(folded, 1.0);
+    out.uv = vertex.uv;
+    out.coord = coord;
+    out.large_noise = large_noise;
+    return out;
+}
+
+// Vertical ray streaks in the colors of the flat aurora, green at the bottom edge and turning
+// to the red of the second layer higher up, or in those of its physical emission model
+@fragment
+fn fragment(in: CurtainVertexOutput) -> @location(0) vec4<f32> {
+    // Rays and flow of `aurora()`, along the ribbon instead of across the sky
+    let rays = smoothstep(0.4, 0.7, sin(in.coord.x * 40.0 + in.large_noise * 8.0 + phases.oscillators_c.y));
+    let flow = sin(in.coord.x * 10.0 + in.large_noise * 3.0 + phases.oscillators_a.y) * 0.5 + 0.5;
+
+    // A sharp lower edge and a long fade upwards, like real curtains, and soft ends
+    let height = in.uv.y;
+    let profile = smoothstep(0.0, 0.05, height) * exp(-3.0 * height);
+    let ends = smoothstep(0.0, 0.1, in.uv.x) * smoothstep(1.0, 0.9, in.uv.x);
+    let intensity = mix(0.3, 1.0, flow) * mix(0.6, 1.0, rays) * profile * ends
+        * smoothstep(0.0, 0.4, in.large_noise + 0.1);
+
+    let low_color = oklab_to_linear_srgb(mix(settings.green, settings.teal, sin(phases.oscillators_a.z) * 0.5 + 0.5));
+    let high_color = oklab_to_linear_srgb(mix(settings.red, settings.pink, sin(phases.oscillators_c.x) * 0.5 + 0.5));
+    let palette_color = mix(low_color, high_color * settings.second_strength, smoothstep(0.3, 0.9, height));
+    let color = mix(palette_color, physical_emission(height), settings.physical_emission);
+
+    // Blended additively: premultiplied color with a zero alpha leaves what is behind untouched
+    return vec4<f32>(color * intensity, 0.0);
+}
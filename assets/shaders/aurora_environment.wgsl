@@ -0,0 +1,98 @@
+#import aurora::core::cube_texel_direction
+
+// Turns a baked aurora cubemap into the two maps of an environment map light: a specular map
+// blurred a bit more at each mip level, and a diffuse irradiance map. Everything happens in the
+// space of the cube textures, the filters do not care about the flipped z.
+
+@group(0) @binding(0) var radiance: texture_cube<f32>;
+@group(0) @binding(1) var radiance_sampler: sampler;
+// One mip level of the map being filtered, in the +X, -X, +Y, -Y, +Z, -Z order of cube textures
+@group(0) @binding(2) var faces: texture_storage_2d_array<rgba16float, write>;
+
+// Directions averaged per texel. The aurora is smooth at environment map resolutions, so a
+// fixed pattern is enough and keeps successive bakes free of noise
+const SAMPLE_COUNT: u32 = 256u;
+const PI: f32 = 3.14159265;
+
+// Low discrepancy points in the unit square, see "Hammersley Points on the Hemisphere" (Holger
+// Dammertz, 2010)
+fn hammersley(i: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(SAMPLE_COUNT), f32(reverseBits(i)) * 2.3283064365386963e-10);
+}
+
+// Turns a direction around +Z into the same direction around `normal`
+fn around(normal: vec3<f32>, v: vec3<f32>) -> vec3<f32> {
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 0.0, 1.0), abs(normal.z) < 0.999);
+    let tangent = normalize(cross(up, normal));
+    let bitangent = cross(normal, tangent);
+    return tangent * v.x + bitangent * v.y + normal * v.z;
+}
+
+fn sample_radiance(direction: vec3<f32>) -> vec3<f32> {
+    return textureSampleLevel(radiance, radiance_sampler, direction, 0.0).rgb;
+}
+
+// Radiance reflected towards `normal` by a GGX lobe of `roughness`, with the view along the
+// normal, see "Real Shading in Unreal Engine 4" (Karis, 2013)
+fn prefiltered_radiance(normal: vec3<f32>, roughness: f32) -> vec3<f32> {
+    if (roughness == 0.0) {
+        return sample_radiance(normal);
+    }
+    let alpha = roughness * roughness;
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i++) {
+        let xi = hammersley(i);
+        let phi = 2.0 * PI * xi.x;
+        let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y));
+        let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+        let half_vector = around(normal, vec3<f32>(sin_theta * cos(phi), sin_theta * sin(phi), cos_theta));
+        let light = reflect(-normal, half_vector);
+        let n_dot_l = dot(normal, light);
+        if (n_dot_l > 0.0) {
+            color += sample_radiance(light) * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    return color / max(weight, 1e-4);
+}
+
+// Cosine weighted average of the radiance over the hemisphere around `normal`, which is what
+// Bevy expects from a diffuse map
+fn irradiance(normal: vec3<f32>) -> vec3<f32> {
+    var color = vec3<f32>(0.0);
+    for (var i = 0u; i < SAMPLE_COUNT; i++) {
+        let xi = hammersley(i);
+        let phi = 2.0 * PI * xi.x;
+        let r = sqrt(xi.y);
+        color += sample_radiance(around(normal, vec3<f32>(r * cos(phi), r * sin(phi), sqrt(1.0 - xi.y))));
+    }
+    return color / f32(SAMPLE_COUNT);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn filter_specular(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(faces);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+
+    // Bevy reads the specular map at the mip level given by the roughness times the index of
+    // the last level, and mip 0 of the specular map has the size of the radiance
+    let levels = log2(f32(textureDimensions(radiance).x));
+    let level = log2(f32(textureDimensions(radiance).x) / f32(size.x));
+    let roughness = clamp(level / max(levels, 1.0), 0.0, 1.0);
+    let normal = cube_texel_direction(id, size);
+    textureStore(faces, id.xy, id.z, vec4<f32>(prefiltered_radiance(normal, roughness), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn filter_diffuse(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(faces);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+
+    let normal = cube_texel_direction(id, size);
+    textureStore(faces, id.xy, id.z, vec4<f32>(irradiance(normal), 1.0));
+}
@@ -0,0 +1,18 @@
+#import aurora::core::{AuroraSettings, AuroraPhases, cube_texel_direction, sky}
+
+@group(0) @binding(0) var<uniform> settings: AuroraSettings;
+@group(0) @binding(1) var<uniform> phases: AuroraPhases;
+// The six faces of the cubemap, in the +X, -X, +Y, -Y, +Z, -Z order of cube textures
+@group(0) @binding(2) var faces: texture_storage_2d_array<rgba16float, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn bake(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(faces);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+
+    // Bevy samples skyboxes and environment maps with the world direction's z flipped
+    let direction = cube_texel_direction(id, size) * vec3<f32>(1.0, 1.0, -1.0);
+    textureStore(faces, id.xy, id.z, vec4<f32>(sky(direction, settings, phases), 1.0));
+}
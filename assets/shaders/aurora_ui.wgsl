@@ -0,0 +1,15 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+#import aurora::core::{AuroraSettings, AuroraPhases, SkyPoint, aurora}
+
+@group(1) @binding(0) var<uniform> settings: AuroraSettings;
+@group(1) @binding(1) var<uniform> phases: AuroraPhases;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // The node's height spans the sky from bottom to top, its width shows as much of the sky as
+    // its aspect ratio allows around the center, so nothing is stretched
+    let aspect = in.size.x / max(in.size.y, 1.0);
+    let elevation = 1.0 - in.uv.y;
+    let coord = vec2<f32>((in.uv.x - 0.5) * aspect + 0.5, elevation);
+    return aurora(SkyPoint(coord, elevation), settings, phases);
+}
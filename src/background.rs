@@ -0,0 +1,295 @@
+//! The aurora drawn over the whole screen behind the opaque geometry, without any mesh.
+
+use bevy::{
+    core_pipeline::{
+        core_3d::{
+            CORE_3D_DEPTH_FORMAT,
+            graph::{Core3d, Node3d},
+        },
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    image::BevyDefault,
+    prelude::*,
+    render::{
+        Render, RenderApp, RenderSet,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        extract_resource::ExtractResourcePlugin,
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{binding_types::uniform_buffer, *},
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        view::{
+            ExtractedView, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset,
+            ViewUniforms,
+        },
+    },
+};
+
+use crate::{AuroraClock, AuroraPhases, AuroraSettings, shader::AURORA_BACKGROUND_SHADER_HANDLE};
+
+/// Draws the aurora behind everything this 3D camera sees, evaluated per pixel from the view
+/// direction.
+///
+/// Unlike a mesh, the sky never stretches whatever the field of view, and it works with
+/// orthographic projections too. The projection is the one of [`AuroraMapping::Dome`], with
+/// the ground below the horizon. The background replaces the clear color wherever no opaque
+/// geometry was drawn.
+///
+/// [`AuroraMapping::Dome`]: crate::AuroraMapping::Dome
+#[derive(Component, ExtractComponent, Debug, Clone, Default, PartialEq)]
+#[require(Camera3d)]
+pub struct AuroraBackground {
+    pub settings: AuroraSettings,
+}
+
+impl AuroraBackground {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self { settings }
+    }
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.add_plugins((
+        ExtractComponentPlugin::<AuroraBackground>::default(),
+        ExtractResourcePlugin::<AuroraClock>::default(),
+    ));
+
+    let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
+        .init_resource::<SpecializedRenderPipelines<BackgroundPipeline>>()
+        .init_resource::<BackgroundUniforms>()
+        .add_systems(
+            Render,
+            (
+                prepare_pipelines.in_set(RenderSet::Prepare),
+                prepare_uniforms.in_set(RenderSet::PrepareResources),
+                prepare_bind_group.in_set(RenderSet::PrepareBindGroups),
+            ),
+        )
+        .add_render_graph_node::<ViewNodeRunner<BackgroundNode>>(Core3d, BackgroundLabel)
+        .add_render_graph_edges(
+            Core3d,
+            (
+                Node3d::MainOpaquePass,
+                BackgroundLabel,
+                Node3d::MainTransmissivePass,
+            ),
+        );
+}
+
+/// Creates the pipeline layout, once the render device exists.
+pub(crate) fn finish(app: &mut App) {
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app.init_resource::<BackgroundPipeline>();
+    }
+}
+
+#[derive(Resource)]
+struct BackgroundPipeline {
+    layout: BindGroupLayout,
+}
+
+impl FromWorld for BackgroundPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "aurora_background_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    uniform_buffer::<ViewUniform>(true),
+                    uniform_buffer::<AuroraSettings>(true),
+                    uniform_buffer::<AuroraPhases>(true),
+                ),
+            ),
+        );
+        Self { layout }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BackgroundPipelineKey {
+    hdr: bool,
+    samples: u32,
+}
+
+impl SpecializedRenderPipeline for BackgroundPipeline {
+    type Key = BackgroundPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let format = if key.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+        RenderPipelineDescriptor {
+            label: Some("aurora_background_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            push_constant_ranges: Vec::new(),
+            vertex: fullscreen_shader_vertex_state(),
+            primitive: PrimitiveState::default(),
+            // The full-screen triangle lies on the far plane, so it only passes where the
+            // depth buffer is still cleared. Bevy's depth is reversed, 0 being the far plane.
+            depth_stencil: Some(DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState {
+                count: key.samples,
+                ..default()
+            },
+            fragment: Some(FragmentState {
+                shader: AURORA_BACKGROUND_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct BackgroundPipelineId(CachedRenderPipelineId);
+
+fn prepare_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BackgroundPipeline>>,
+    pipeline: Res<BackgroundPipeline>,
+    views: Query<(Entity, &ExtractedView, &Msaa), With<AuroraBackground>>,
+) {
+    for (entity, view, msaa) in &views {
+        let key = BackgroundPipelineKey {
+            hdr: view.hdr,
+            samples: msaa.samples(),
+        };
+        let id = pipelines.specialize(&pipeline_cache, &pipeline, key);
+        commands.entity(entity).insert(BackgroundPipelineId(id));
+    }
+}
+
+/// Settings and phases of every view, rewritten each frame.
+#[derive(Resource, Default)]
+struct BackgroundUniforms {
+    settings: DynamicUniformBuffer<AuroraSettings>,
+    phases: DynamicUniformBuffer<AuroraPhases>,
+}
+
+/// Where the uniforms of a view are in [`BackgroundUniforms`].
+#[derive(Component)]
+struct BackgroundUniformOffsets {
+    settings: u32,
+    phases: u32,
+}
+
+fn prepare_uniforms(
+    mut commands: Commands,
+    clock: Res<AuroraClock>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut uniforms: ResMut<BackgroundUniforms>,
+    views: Query<(Entity, &AuroraBackground)>,
+) {
+    let uniforms = &mut *uniforms;
+    uniforms.settings.clear();
+    uniforms.phases.clear();
+    for (entity, background) in &views {
+        let phases = AuroraPhases::new(clock.elapsed(), &background.settings);
+        commands.entity(entity).insert(BackgroundUniformOffsets {
+            settings: uniforms.settings.push(&background.settings),
+            phases: uniforms.phases.push(&phases),
+        });
+    }
+    uniforms
+        .settings
+        .write_buffer(&render_device, &render_queue);
+    uniforms.phases.write_buffer(&render_device, &render_queue);
+}
+
+/// Bind group shared by every view, told apart by dynamic offsets.
+#[derive(Resource)]
+struct BackgroundBindGroup(BindGroup);
+
+fn prepare_bind_group(
+    mut commands: Commands,
+    pipeline: Res<BackgroundPipeline>,
+    render_device: Res<RenderDevice>,
+    view_uniforms: Res<ViewUniforms>,
+    uniforms: Res<BackgroundUniforms>,
+) {
+    let (Some(view), Some(settings), Some(phases)) = (
+        view_uniforms.uniforms.binding(),
+        uniforms.settings.binding(),
+        uniforms.phases.binding(),
+    ) else {
+        commands.remove_resource::<BackgroundBindGroup>();
+        return;
+    };
+    commands.insert_resource(BackgroundBindGroup(render_device.create_bind_group(
+        "aurora_background_bind_group",
+        &pipeline.layout,
+        &BindGroupEntries::sequential((view, settings, phases)),
+    )));
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct BackgroundLabel;
+
+#[derive(Default)]
+struct BackgroundNode;
+
+impl ViewNode for BackgroundNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        &'static ViewUniformOffset,
+        &'static BackgroundUniformOffsets,
+        &'static BackgroundPipelineId,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, depth, view_offset, offsets, pipeline_id): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // Nothing is drawn until the shader has compiled
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(pipeline_id.0)
+        else {
+            return Ok(());
+        };
+        let Some(bind_group) = world.get_resource::<BackgroundBindGroup>() else {
+            return Ok(());
+        };
+
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("aurora_background"),
+            color_attachments: &[Some(target.get_color_attachment())],
+            depth_stencil_attachment: Some(depth.get_attachment(StoreOp::Store)),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_render_pipeline(pipeline);
+        pass.set_bind_group(
+            0,
+            &bind_group.0,
+            &[view_offset.offset, offsets.settings, offsets.phases],
+        );
+        pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! Command line interface of the demo binary.
+
+use std::{path::PathBuf, process::ExitCode, time::Instant};
+
+use aurora::{
+    AuroraPreset, AuroraSettings,
+    export::{ExportFormat, FrameWriter, MIN_FPS},
+    render,
+};
+
+const RENDER_USAGE: &str = "\
+Usage: aurora render [options]
+
+Renders the aurora on the CPU and writes images, animations or video, no window or GPU needed.
+Frames are spaced by exactly 1/fps seconds, however long they take to render.
+
+Options:
+  --time <SECONDS>  Time of the first frame [default: 0]
+  --size <WxH>      Image size in pixels [default: 1920x1080]
+  --out <PATH>      Output file [default: frame.png]. A run of `#` in a .png path writes
+                    one PNG per frame, replaced by the zero padded frame number.
+                    Otherwise a .gif or .png/.apng path writes an animation,
+                    and a .y4m path, or `-` for stdout, an uncompressed video
+  --preset <FILE>   `.aurora.ron` preset to render, read from disk
+  --seed <N>        Noise seed, overrides the one from the preset
+  --loop <SECONDS>  Loop period, overrides the one from the preset
+  --frames <N>      Number of frames to render [default: 1]
+  --duration <SECONDS>
+                    Length of the animation, instead of `--frames`
+  --fps <N>         Frame rate of a sequence [default: 30]";
+
+/// Options of the `render` subcommand.
+#[derive(Debug)]
+pub struct RenderArgs {
+    time: f64,
+    width: u32,
+    height: u32,
+    out: String,
+    preset: Option<PathBuf>,
+    seed: Option<u32>,
+    loop_period: Option<f32>,
+    frames: u32,
+    duration: Option<f64>,
+    fps: f64,
+}
+
+impl Default for RenderArgs {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            width: 1920,
+            height: 1080,
+            out: "frame.png".to_owned(),
+            preset: None,
+            seed: None,
+            loop_period: None,
+            frames: 1,
+            duration: None,
+            fps: 30.0,
+        }
+    }
+}
+
+impl RenderArgs {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut frames = None;
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{flag}`"))
+            };
+            match flag.as_str() {
+                "--time" => parsed.time = parse_number(&flag, &value()?)?,
+                "--size" => (parsed.width, parsed.height) = parse_size(&value()?)?,
+                "--out" => parsed.out = value()?,
+                "--preset" => parsed.preset = Some(value()?.into()),
+                "--seed" => parsed.seed = Some(parse_number(&flag, &value()?)?),
+                "--loop" => parsed.loop_period = Some(parse_number(&flag, &value()?)?),
+                "--frames" => frames = Some(parse_number(&flag, &value()?)?),
+                "--duration" => parsed.duration = Some(parse_number(&flag, &value()?)?),
+                "--fps" => parsed.fps = parse_number(&flag, &value()?)?,
+                _ => return Err(format!("unknown option `{flag}`")),
+            }
+        }
+
+        if !(parsed.fps.is_finite() && parsed.fps >= MIN_FPS) {
+            return Err(format!("`--fps` must be at least {MIN_FPS}"));
+        }
+        parsed.frames = match (frames, parsed.duration) {
+            (Some(_), Some(_)) => {
+                return Err("`--frames` and `--duration` cannot be used together".to_owned());
+            }
+            (Some(0), None) => return Err("`--frames` must be at least 1".to_owned()),
+            (Some(frames), None) => frames,
+            // A duration shorter than half a frame still renders one
+            (None, Some(duration)) if duration > 0.0 && duration.is_finite() => {
+                ((duration * parsed.fps).round() as u32).max(1)
+            }
+            (None, Some(_)) => return Err("`--duration` must be positive".to_owned()),
+            (None, None) => 1,
+        };
+        ExportFormat::from_path(&parsed.out).map_err(|error| error.to_string())?;
+        Ok(parsed)
+    }
+
+    fn settings(&self) -> Result<AuroraSettings, String> {
+        let mut settings = match &self.preset {
+            Some(path) => {
+                let bytes = std::fs::read(path)
+                    .map_err(|error| format!("could not read `{}`: {error}", path.display()))?;
+                AuroraPreset::from_ron(&bytes)
+                    .map_err(|error| format!("`{}`: {error}", path.display()))?
+                    .settings
+            }
+            None => AuroraSettings::default(),
+        };
+        if let Some(seed) = self.seed {
+            settings.seed = seed;
+        }
+        if let Some(loop_period) = self.loop_period {
+            settings.loop_period = loop_period;
+        }
+        Ok(settings)
+    }
+}
+
+/// Runs `aurora render` with the arguments following the subcommand.
+pub fn render(args: impl Iterator<Item = String>) -> ExitCode {
+    let args: Vec<String> = args.collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{RENDER_USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let result = RenderArgs::parse(args.into_iter())
+        .map_err(|error| format!("{error}\n\n{RENDER_USAGE}"))
+        .and_then(|args| run_render(&args));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_render(args: &RenderArgs) -> Result<(), String> {
+    let settings = args.settings()?;
+    let mut writer = FrameWriter::create(&args.out, args.width, args.height, args.frames, args.fps)
+        .map_err(|error| error.to_string())?;
+    for frame in 0..args.frames {
+        let start = Instant::now();
+        let time = args.time + f64::from(frame) / args.fps;
+        let rendered = render::render_frame(&settings, time, args.width, args.height);
+        let path = writer.write(&rendered).map_err(|error| error.to_string())?;
+        eprintln!(
+            "wrote frame {frame} to {path} (t = {time:.3}s) in {:.2?}",
+            start.elapsed()
+        );
+    }
+    writer.finish().map_err(|error| error.to_string())
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{flag}`"))
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid size `{value}`, expected e.g. `1920x1080`");
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
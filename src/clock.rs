@@ -0,0 +1,99 @@
+//! The clock driving the aurora animation.
+
+use bevy::{prelude::*, render::extract_resource::ExtractResource};
+
+use crate::{AuroraPhases, material::AuroraMaterial};
+
+/// Animation time of every aurora, fed to the shaders as [`AuroraPhases`].
+///
+/// Unlike Bevy's global shader time, it can be paused, played at any rate (including
+/// backwards), jumped to an absolute time and stepped frame by frame.
+#[derive(Resource, ExtractResource, Debug, Clone, PartialEq)]
+pub struct AuroraClock {
+    elapsed: f64,
+    rate: f64,
+    paused: bool,
+}
+
+impl Default for AuroraClock {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            rate: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl AuroraClock {
+    /// Animation time, in seconds.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Jumps to `seconds` of animation time.
+    pub fn set_elapsed(&mut self, seconds: f64) {
+        self.elapsed = seconds;
+    }
+
+    /// Animation seconds per real second, negative values play backwards.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Moves the animation by `seconds`, whether paused or not and regardless of the rate.
+    /// Useful to step frame by frame while paused.
+    pub fn step(&mut self, seconds: f64) {
+        self.elapsed += seconds;
+    }
+
+    /// Advances the clock by `delta` real seconds, honoring pause and rate.
+    pub fn tick(&mut self, delta: f64) {
+        if !self.paused {
+            self.elapsed += delta * self.rate;
+        }
+    }
+}
+
+pub(crate) fn advance_clock(time: Res<Time>, mut clock: ResMut<AuroraClock>) {
+    clock.tick(time.delta_secs_f64());
+}
+
+/// Updates the phases of every material from the clock, touching only those that are out of date.
+pub(crate) fn sync_materials<M: AuroraMaterial>(
+    clock: Res<AuroraClock>,
+    mut materials: ResMut<Assets<M>>,
+) {
+    let stale: Vec<_> = materials
+        .iter()
+        .filter_map(|(id, material)| {
+            let phases = AuroraPhases::new(clock.elapsed(), material.settings());
+            (*material.phases() != phases).then_some((id, phases))
+        })
+        .collect();
+    for (id, phases) in stale {
+        if let Some(material) = materials.get_mut(id) {
+            *material.phases_mut() = phases;
+        }
+    }
+}
@@ -0,0 +1,478 @@
+//! CPU reference implementation of the aurora shader.
+//!
+//! Every function mirrors its namesake in the shaders, mostly `aurora_core.wgsl`, line for
+//! line, so the look can be evaluated without a GPU: in tests, golden image comparisons or
+//! offline renders. Any change to the shaders must be reflected here.
+
+use bevy::math::{Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
+
+use crate::{AuroraMapping, AuroraPhases, AuroraSettings, phases::NOISE_PERIOD};
+
+/// The shader writes 2π as `6.28`, which matters for the surge and pulse timing.
+#[allow(clippy::approx_constant)]
+const SHADER_TAU: f32 = 6.28;
+
+/// PCG integer hash, see "Hash Functions for GPU Rendering" (Jarzynski & Olano, 2020).
+pub fn pcg(v: u32) -> u32 {
+    let state = v.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+/// OKLab to linear sRGB conversion.
+// Constants are kept verbatim from the shader
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_linear_srgb(c: Vec3) -> Vec3 {
+    let (l, a, b) = (c.x, c.y, c.z);
+
+    let l_ = l + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = l - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = l - 0.0894841775 * a - 1.2914855480 * b;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    Vec3::new(
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    )
+}
+
+/// Spectral lines of the aurora, in nanometers: the green and red lines of atomic oxygen and
+/// the blue line of ionized molecular nitrogen.
+pub const OXYGEN_GREEN_LINE: f32 = 557.7;
+pub const OXYGEN_RED_LINE: f32 = 630.0;
+pub const NITROGEN_BLUE_LINE: f32 = 427.8;
+
+/// One lobe of [`cie_xyz`]'s fit.
+fn cie_lobe(wavelength: f32, peak: f32, width_below: f32, width_above: f32) -> f32 {
+    let width = if wavelength < peak {
+        width_below
+    } else {
+        width_above
+    };
+    let t = (wavelength - peak) / width;
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 2° color matching functions at a wavelength in nanometers, from the multi-lobe
+/// fit of Wyman, Sloan & Shirley (2013).
+pub fn cie_xyz(wavelength: f32) -> Vec3 {
+    let x = 1.056 * cie_lobe(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * cie_lobe(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * cie_lobe(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * cie_lobe(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * cie_lobe(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * cie_lobe(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * cie_lobe(wavelength, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+/// CIE XYZ to linear sRGB conversion, D65 white point.
+// Constants are kept verbatim from the shader
+#[allow(clippy::excessive_precision)]
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Vec3 {
+    Vec3::new(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    )
+}
+
+/// Linear sRGB color of a spectral line at its chromaticity, clipped to the sRGB gamut.
+pub fn line_color(wavelength: f32) -> Vec3 {
+    let xyz = cie_xyz(wavelength);
+    xyz_to_linear_srgb(xyz / (xyz.x + xyz.y + xyz.z)).max(Vec3::ZERO)
+}
+
+/// Light given off at `altitude` through the aurora, from 0 at its lower edge to 1 at its top,
+/// in linear RGB.
+pub fn physical_emission(altitude: f32) -> Vec3 {
+    let green = smoothstep(0.1, 0.3, altitude) * (-4.0 * (altitude - 0.4).max(0.0)).exp();
+    let red = 0.35 * smoothstep(0.5, 0.9, altitude);
+    let fringe = (altitude - 0.15) / 0.08;
+    let blue = 0.6 * (-fringe * fringe).exp() + 0.25 * smoothstep(0.75, 1.0, altitude);
+    line_color(OXYGEN_GREEN_LINE) * green
+        + line_color(OXYGEN_RED_LINE) * red
+        + line_color(NITROGEN_BLUE_LINE) * blue
+}
+
+/// The seeded noise functions of the shader.
+#[derive(Debug, Clone, Copy)]
+pub struct Noise {
+    seed_hash: u32,
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed_hash: pcg(seed),
+        }
+    }
+
+    /// Random value in `[0, 1)` at an integer lattice point, repeating every
+    /// [`NOISE_PERIOD`](crate::phases::NOISE_PERIOD) cells.
+    pub fn hash(&self, p: Vec2) -> f32 {
+        let cell_x = p.x.floor() as i32 as u32 & (NOISE_PERIOD - 1);
+        let cell_y = p.y.floor() as i32 as u32 & (NOISE_PERIOD - 1);
+        let h = pcg(cell_x ^ pcg(cell_y ^ self.seed_hash));
+        (h >> 8) as f32 / 16777216.0
+    }
+
+    /// Value noise with cubic Hermite interpolation.
+    pub fn noise21(&self, p: Vec2) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+
+        let u = f * f * (3.0 - 2.0 * f);
+
+        let a = self.hash(i + Vec2::new(0.0, 0.0));
+        let b = self.hash(i + Vec2::new(1.0, 0.0));
+        let c = self.hash(i + Vec2::new(0.0, 1.0));
+        let d = self.hash(i + Vec2::new(1.0, 1.0));
+
+        mix(mix(a, b, u.x), mix(c, d, u.x), u.y)
+    }
+
+    /// Fractional Brownian motion over `octaves` layers of [`Noise::noise21`].
+    pub fn fbm(&self, p: Vec2, octaves: i32) -> f32 {
+        let mut value = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = 2.0;
+
+        for _ in 0..octaves {
+            value += amplitude * self.noise21(p * frequency);
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        value
+    }
+
+    /// Subtle space dust behind the aurora, `offsets` are the horizontal drift of its layers.
+    pub fn nebula(&self, coord: Vec2, offsets: Vec3, strength: f32) -> Vec3 {
+        let nebula_noise = self.fbm(coord * 4.0 + Vec2::new(offsets.x, 0.0), 3) * strength;
+
+        let hue = self.fbm(coord * 2.0 + Vec2::new(offsets.y, 0.0), 2);
+
+        let nebula_color = Vec3::new(0.02, 0.035, 0.05).lerp(Vec3::new(0.04, 0.02, 0.06), hue);
+
+        nebula_color
+            * nebula_noise
+            * smoothstep(
+                0.4,
+                0.6,
+                self.noise21(coord * 3.0 + Vec2::new(offsets.z, 0.0)),
+            )
+    }
+}
+
+/// Where a point is in the sky, see [`sky_point`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyPoint {
+    /// Coordinates driving the aurora patterns.
+    pub coord: Vec2,
+    /// Places the layers and the background gradient, from 0 at the bottom or horizon to 1 at
+    /// the top or zenith.
+    pub elevation: f32,
+}
+
+const DOME_HORIZON_OFFSET: f32 = 0.3;
+const DOME_SCALE: f32 = 0.5;
+const GROUND_COLOR: Vec3 = Vec3::new(0.002, 0.004, 0.008);
+
+/// Maps mesh UVs to a point in the sky, like the shader's `sky_point()` with `mapping`'s
+/// shader def and a square surface.
+///
+/// The mappings reading world, object or screen positions take `uv` as the flat coordinates
+/// they derive from them, a single projection for [`AuroraMapping::Triplanar`], and
+/// [`AuroraMapping::WorldProjected`] like [`AuroraMapping::Dome`]. The curtains of
+/// [`AuroraMapping::Volumetric`] are not mirrored, it maps like the dome too.
+pub fn sky_point(uv: Vec2, mapping: AuroraMapping) -> SkyPoint {
+    match mapping {
+        AuroraMapping::Uv
+        | AuroraMapping::WorldXz
+        | AuroraMapping::Screen
+        | AuroraMapping::Triplanar => SkyPoint {
+            coord: uv,
+            elevation: uv.y,
+        },
+        AuroraMapping::Dome | AuroraMapping::WorldProjected | AuroraMapping::Volumetric => {
+            let azimuth = uv.x * std::f32::consts::TAU;
+            let altitude = uv.y * std::f32::consts::FRAC_PI_2;
+            direction_point(Vec3::new(
+                altitude.cos() * azimuth.sin(),
+                altitude.sin(),
+                altitude.cos() * azimuth.cos(),
+            ))
+        }
+    }
+}
+
+/// Maps a unit direction above the horizon to a point in the sky.
+pub fn direction_point(direction: Vec3) -> SkyPoint {
+    let plane = direction.xz() / (direction.y + DOME_HORIZON_OFFSET);
+    let elevation = direction.y.clamp(0.0, 1.0).asin() / std::f32::consts::FRAC_PI_2;
+    SkyPoint {
+        coord: plane * DOME_SCALE + 0.5,
+        elevation,
+    }
+}
+
+/// The whole sky seen in a world space direction, fading into the ground below the horizon.
+/// Returns linear RGB.
+pub fn sky(direction: Vec3, phases: &AuroraPhases, settings: &AuroraSettings) -> Vec3 {
+    let above = Vec3::new(direction.x, direction.y.max(0.0) + 1e-6, direction.z).normalize();
+    let color = aurora(direction_point(above), phases, settings).truncate();
+    GROUND_COLOR.lerp(color, smoothstep(-0.1, 0.0, direction.y))
+}
+
+/// Evaluates the aurora at `uv` for the given clock `time` in seconds, like the
+/// shader's `fragment()` does for a pixel of a UV mapped surface. Returns linear RGB and alpha.
+pub fn fragment(uv: Vec2, time: f64, settings: &AuroraSettings) -> Vec4 {
+    fragment_with_phases(uv, &AuroraPhases::new(time, settings), settings)
+}
+
+/// [`fragment`] with precomputed phases, to avoid recomputing them for every pixel.
+pub fn fragment_with_phases(uv: Vec2, phases: &AuroraPhases, settings: &AuroraSettings) -> Vec4 {
+    aurora(sky_point(uv, AuroraMapping::Uv), phases, settings)
+}
+
+/// The aurora at a point in the sky, like the shader's `aurora()`.
+pub fn aurora(point: SkyPoint, phases: &AuroraPhases, settings: &AuroraSettings) -> Vec4 {
+    let noise = Noise::new(settings.seed);
+
+    let SkyPoint { coord, elevation } = point;
+
+    let y_stretch = settings.y_stretch;
+    let waviness = settings.waviness;
+
+    let noise_coord = Vec2::new(coord.x * 2.0, coord.y * y_stretch) + phases.drift_a.xy();
+    let large_noise = noise.fbm(noise_coord, 3) * waviness;
+
+    let wave_effect = (coord.y * 15.0 + phases.oscillators_a.x + large_noise * 5.0).sin() * 0.05;
+    let displaced_x = coord.x + wave_effect;
+
+    let flow = (displaced_x * 10.0 + large_noise * 3.0 + phases.oscillators_a.y).sin() * 0.5 + 0.5;
+    let height_mask = smoothstep(
+        0.0,
+        settings.height_falloff,
+        1.0 - (elevation - settings.height).abs() * 2.0,
+    );
+
+    let intensity = flow * height_mask * smoothstep(0.0, 0.4, large_noise + 0.1);
+
+    let dist_center = coord.distance(Vec2::new(0.5, 0.5));
+    let dist_factor = smoothstep(0.0, 1.2, dist_center);
+
+    let small_noise = noise.fbm(noise_coord * 4.0 + Vec2::new(phases.drift_d.x, 0.0), 2) * 0.4;
+    let detail_intensity = small_noise * intensity * 0.8;
+
+    let fine_noise = noise.fbm(noise_coord * 8.0 + phases.drift_a.zw(), 3) * 0.3;
+    let fine_detail = fine_noise * intensity * smoothstep(0.2, 0.8, large_noise) * 0.6;
+
+    let surge_phase = noise.hash(Vec2::new(phases.cycles.x, 1.0));
+
+    let surge_x = coord.x * 3.0 + large_noise * 2.0;
+    let surge_y = coord.y * 4.0 + large_noise * 3.0;
+
+    let surge_pattern1 = smoothstep(
+        0.45,
+        0.65,
+        (phases.oscillators_a.z + surge_x + surge_y).sin(),
+    );
+    let surge_pattern2 = smoothstep(
+        0.55,
+        0.75,
+        (phases.oscillators_a.w - surge_x + surge_y * 2.0).sin(),
+    );
+    let surge_pattern3 = smoothstep(
+        0.65,
+        0.85,
+        (phases.oscillators_b.x + surge_x * 2.0 - surge_y).cos(),
+    );
+
+    let subtle_surge_timing = smoothstep(
+        0.8,
+        0.95,
+        (phases.cycles.y * SHADER_TAU + surge_phase * 10.0).sin(),
+    );
+
+    let intensity_surge = surge_pattern1.max(surge_pattern2.max(surge_pattern3))
+        * intensity
+        * settings.surge_strength
+        * subtle_surge_timing;
+
+    let t1 = phases.oscillators_a.z.sin() * 0.5 + 0.5;
+    let t2 = phases.oscillators_a.w.cos() * 0.5 + 0.5;
+    let t3 = (phases.oscillators_b.y + dist_center).sin() * 0.5 + 0.5;
+
+    let green = settings.green;
+    let teal = settings.teal;
+    let blue = settings.blue;
+    let purple = settings.purple;
+    let red = settings.red;
+    let pink = settings.pink;
+
+    let color1 = green.lerp(teal, t1);
+    let color2 = blue.lerp(purple, t2);
+    let mixed_color = color1.lerp(
+        color2,
+        t3 * dist_factor + detail_intensity + fine_detail * 0.5,
+    );
+
+    let micro_noise_coord = noise_coord * 12.0 + phases.drift_b.xy();
+    let micro_noise = noise.fbm(micro_noise_coord, 2) * 0.07 * intensity;
+    let micro_detail =
+        smoothstep(0.35, 0.65, (coord.y * 30.0 + large_noise * 10.0).sin()) * intensity * 0.08;
+
+    let main_intensity = intensity
+        + detail_intensity * 0.7
+        + fine_detail * 0.6
+        + micro_noise
+        + micro_detail
+        + intensity_surge;
+    let aurora_lab_color = mixed_color * main_intensity;
+
+    let surge_color = green.lerp(Vec3::new(0.9, -0.1, 0.2), 0.3);
+    let surge_contribution = surge_color * intensity_surge * 0.7;
+
+    let aurora_with_surge = aurora_lab_color + surge_contribution;
+
+    let emission = physical_emission((elevation - settings.height + 0.5).clamp(0.0, 1.0));
+    let rgb_color = oklab_to_linear_srgb(aurora_with_surge).lerp(
+        emission * (main_intensity + intensity_surge * 0.7),
+        settings.physical_emission,
+    );
+
+    let glow = intensity * settings.glow_strength;
+    let glow_color = Vec3::new(0.05, 0.1, 0.2).lerp(rgb_color, intensity);
+
+    let cross_detail = noise.fbm(
+        Vec2::new(coord.y * 6.0, coord.x * 12.0) + phases.drift_b.zw(),
+        2,
+    ) * 0.2;
+    let cross_effect = cross_detail * smoothstep(0.0, 0.6, intensity) * 0.3;
+
+    let wisps = smoothstep(
+        0.3,
+        0.7,
+        (coord.y * 30.0 + large_noise * 10.0 + phases.oscillators_a.w).sin(),
+    ) * intensity
+        * settings.wisp_strength;
+
+    let aurora2_offset = Vec2::new(0.2, -0.1);
+    let aurora2_noise_coord = Vec2::new(
+        coord.x * 1.5 + aurora2_offset.x,
+        coord.y * 2.5 + aurora2_offset.y,
+    ) + phases.drift_c.xy();
+
+    let aurora2_detail_coord = aurora2_noise_coord * 3.0 + phases.drift_c.zw();
+    let aurora2_detail = noise.fbm(aurora2_detail_coord, 2) * 0.5;
+
+    let aurora2_large_noise = noise.fbm(aurora2_noise_coord, 3) * 0.7;
+    let aurora2_wave =
+        (coord.y * 10.0 + phases.oscillators_b.z + aurora2_large_noise * 4.0).sin() * 0.07;
+    let aurora2_displaced_x = coord.x + aurora2_wave;
+
+    let aurora2_flow =
+        (aurora2_displaced_x * 8.0 + aurora2_large_noise * 2.0 + phases.oscillators_b.w).sin()
+            * 0.5
+            + 0.5;
+    let aurora2_height_mask = smoothstep(
+        0.0,
+        settings.second_height_falloff,
+        1.0 - (elevation - settings.second_height).abs() * 2.0,
+    );
+    let aurora2_intensity =
+        aurora2_flow * aurora2_height_mask * smoothstep(0.0, 0.4, aurora2_large_noise);
+
+    let t4 = phases.oscillators_c.x.sin() * 0.5 + 0.5;
+    let aurora2_color_mix = red.lerp(pink, t4);
+
+    let aurora2_strength =
+        aurora2_intensity * settings.second_strength + aurora2_detail * aurora2_intensity * 0.4;
+    let aurora2_lab_color = aurora2_color_mix * aurora2_strength;
+    let aurora2_emission =
+        physical_emission((elevation - settings.second_height + 0.9).clamp(0.0, 1.0));
+    let aurora2_rgb = oklab_to_linear_srgb(aurora2_lab_color).lerp(
+        aurora2_emission * aurora2_strength,
+        settings.physical_emission,
+    );
+
+    let aurora2_wisps = smoothstep(
+        0.4,
+        0.6,
+        (coord.y * 40.0 + aurora2_large_noise * 15.0 + phases.oscillators_a.z).sin(),
+    ) * aurora2_intensity
+        * 0.2;
+    let aurora2_wisp_color = oklab_to_linear_srgb(red.lerp(pink, 0.3) * aurora2_wisps)
+        .lerp(aurora2_emission * aurora2_wisps, settings.physical_emission);
+
+    let pulse_phase = noise.hash(Vec2::new(phases.cycles.z, 0.0));
+
+    let curtain_x = coord.x * 15.0 + large_noise * 5.0;
+    let pulse_visibility = smoothstep(
+        0.85,
+        0.95,
+        (phases.cycles.w * SHADER_TAU + pulse_phase * SHADER_TAU).sin(),
+    );
+    let curtain_pattern = smoothstep(0.45, 0.65, (curtain_x + phases.oscillators_a.y).sin())
+        * smoothstep(0.3, 0.7, intensity)
+        * settings.curtain_strength
+        * pulse_visibility;
+
+    let rays_pattern = smoothstep(
+        0.40,
+        0.70,
+        (coord.y * 40.0 + large_noise * 8.0 + phases.oscillators_c.y).sin()
+            * smoothstep(0.45, 0.65, (curtain_x + phases.oscillators_a.y).sin()),
+    ) * intensity
+        * settings.ray_strength
+        * pulse_visibility;
+
+    let curtain_color = oklab_to_linear_srgb(green.lerp(teal, 0.5) * curtain_pattern)
+        .lerp(emission * curtain_pattern, settings.physical_emission);
+    let rays_color = oklab_to_linear_srgb(green.lerp(blue, 0.4) * rays_pattern)
+        .lerp(emission * rays_pattern, settings.physical_emission);
+
+    let aurora_color = rgb_color
+        + glow_color * glow
+        + Vec3::new(0.1, 0.15, 0.2) * cross_effect
+        + rgb_color * wisps
+        + aurora2_rgb
+        + aurora2_wisp_color
+        + curtain_color
+        + rays_color;
+
+    let nebula_color = noise.nebula(coord, phases.drift_d.yzw(), settings.nebula_strength);
+
+    let vignette = smoothstep(1.2, 0.5, (coord - Vec2::splat(0.5)).length());
+
+    let bg_gradient =
+        Vec3::new(0.0, 0.01, 0.03).lerp(Vec3::new(0.01, 0.03, 0.07), elevation * 0.7) * vignette;
+
+    let sky_color = bg_gradient + nebula_color;
+
+    // `max` then `min` rather than `clamp`, which panics on an inverted range
+    let aurora_blend_factor = (intensity * settings.blend_strength)
+        .max(0.0)
+        .min(settings.blend_cap);
+
+    let with_aurora = sky_color.lerp(aurora_color, aurora_blend_factor);
+
+    with_aurora.extend(intensity * settings.alpha_scale + settings.alpha_base)
+}
+
+/// WGSL `smoothstep`, which unlike a clamped Hermite also accepts `edge0 > edge1`.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// WGSL `mix` for scalars.
+pub fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
@@ -0,0 +1,384 @@
+//! Baking the aurora into cubemaps on the GPU, seen from the center of the sky.
+//!
+//! Main world systems queue [`CubemapBake`]s every frame; they are extracted to the render
+//! world and run by a compute node before any camera renders, so the cubemaps are up to date
+//! by the time they are sampled. A bake can also filter its cubemap into the diffuse and
+//! specular maps of an environment map light. The render world reports back which of those
+//! were dispatched through [`BakedEnvironments`].
+
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::{
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+        graph::CameraDriverLabel,
+        render_asset::RenderAssets,
+        render_graph::{self, RenderGraph, RenderLabel},
+        render_resource::{
+            binding_types::{sampler, texture_cube, texture_storage_2d_array, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::GpuImage,
+    },
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    AuroraPhases, AuroraSettings,
+    shader::{AURORA_ENVIRONMENT_SHADER_HANDLE, AURORA_SKYBOX_SHADER_HANDLE},
+};
+
+/// Size of the compute workgroups in `aurora_skybox.wgsl` and `aurora_environment.wgsl`.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Cubemaps are stored in half floats so the aurora can exceed 1 and still be tone mapped.
+const FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// A request to render the aurora into every face of a cubemap made by [`cubemap_image`].
+#[derive(Debug, Clone)]
+pub(crate) struct CubemapBake {
+    pub image: AssetId<Image>,
+    pub settings: AuroraSettings,
+    pub phases: AuroraPhases,
+    /// Environment maps to filter from the cubemap once baked.
+    pub environment: Option<EnvironmentMaps>,
+}
+
+/// Cubemaps made by [`cubemap_image`] to hold an environment map light.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EnvironmentMaps {
+    pub diffuse: AssetId<Image>,
+    /// Needs a full mip chain, with mip 0 the size of the baked cubemap.
+    pub specular: AssetId<Image>,
+}
+
+/// The bakes to run this frame, cleared at the start of every frame.
+#[derive(Resource, Debug, Clone, Default)]
+pub(crate) struct CubemapBakes(pub Vec<CubemapBake>);
+
+/// Cubemaps of bakes with an environment that were dispatched, shared by both worlds.
+///
+/// A bake is dropped while its images are not on the GPU or its pipelines are compiling, the
+/// main world takes its cubemap out of here to know it went through.
+#[derive(Resource, Debug, Clone, Default)]
+pub(crate) struct BakedEnvironments(Arc<Mutex<HashSet<AssetId<Image>>>>);
+
+impl BakedEnvironments {
+    /// Whether the bake of `image` was dispatched since the last call.
+    pub fn take(&self, image: AssetId<Image>) -> bool {
+        self.0.lock().unwrap().remove(&image)
+    }
+
+    fn insert(&self, image: AssetId<Image>) {
+        self.0.lock().unwrap().insert(image);
+    }
+}
+
+/// Number of mip levels of a cubemap with faces of `resolution` pixels, down to 1x1.
+pub(crate) fn mip_level_count(resolution: u32) -> u32 {
+    resolution.max(1).ilog2() + 1
+}
+
+/// An empty cubemap the aurora can be baked into, with faces of `resolution` pixels.
+///
+/// Its contents only live on the GPU.
+pub(crate) fn cubemap_image(resolution: u32, mip_level_count: u32) -> Image {
+    let resolution = resolution.max(1);
+    let size = Extent3d {
+        width: resolution,
+        height: resolution,
+        depth_or_array_layers: 6,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0; 8],
+        FORMAT,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // The upload expects data for every mip level
+    image.texture_descriptor.mip_level_count = mip_level_count;
+    let texels: u32 = (0..mip_level_count)
+        .map(|level| (resolution >> level).max(1).pow(2) * 6)
+        .sum();
+    image.data.resize(texels as usize * 8, 0);
+    image.texture_descriptor.usage |= TextureUsages::STORAGE_BINDING;
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    image
+}
+
+pub(crate) fn build(app: &mut App) {
+    let baked = BakedEnvironments::default();
+    app.init_resource::<CubemapBakes>()
+        .insert_resource(baked.clone())
+        .add_systems(First, clear_bakes);
+
+    let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
+        .insert_resource(baked)
+        .init_resource::<CubemapBakes>()
+        .init_resource::<CubemapBakeBuffers>()
+        .init_resource::<CubemapBakeBindGroups>()
+        .add_systems(ExtractSchedule, extract_bakes)
+        .add_systems(
+            Render,
+            prepare_bind_groups.in_set(RenderSet::PrepareBindGroups),
+        );
+    let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+    render_graph.add_node(CubemapBakeLabel, CubemapBakeNode);
+    render_graph.add_node_edge(CubemapBakeLabel, CameraDriverLabel);
+}
+
+/// Creates the pipelines, once the render device exists.
+pub(crate) fn finish(app: &mut App) {
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app.init_resource::<CubemapBakePipelines>();
+    }
+}
+
+fn clear_bakes(mut bakes: ResMut<CubemapBakes>) {
+    bakes.0.clear();
+}
+
+fn extract_bakes(mut commands: Commands, bakes: Extract<Res<CubemapBakes>>) {
+    commands.insert_resource(bakes.clone());
+}
+
+#[derive(Resource)]
+struct CubemapBakePipelines {
+    bake_layout: BindGroupLayout,
+    bake: CachedComputePipelineId,
+    filter_layout: BindGroupLayout,
+    filter_sampler: Sampler,
+    filter_specular: CachedComputePipelineId,
+    filter_diffuse: CachedComputePipelineId,
+}
+
+impl FromWorld for CubemapBakePipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bake_layout = render_device.create_bind_group_layout(
+            "aurora_cubemap_bake_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<AuroraSettings>(false),
+                    uniform_buffer::<AuroraPhases>(false),
+                    texture_storage_2d_array(FORMAT, StorageTextureAccess::WriteOnly),
+                ),
+            ),
+        );
+        let filter_layout = render_device.create_bind_group_layout(
+            "aurora_cubemap_filter_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_cube(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    texture_storage_2d_array(FORMAT, StorageTextureAccess::WriteOnly),
+                ),
+            ),
+        );
+        let filter_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("aurora_cubemap_filter_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let queue =
+            |layout: &BindGroupLayout, shader: &Handle<Shader>, entry_point: &'static str| {
+                pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some(format!("aurora_cubemap_{entry_point}_pipeline").into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: Vec::new(),
+                    shader: shader.clone(),
+                    shader_defs: Vec::new(),
+                    entry_point: entry_point.into(),
+                    zero_initialize_workgroup_memory: false,
+                })
+            };
+        Self {
+            bake: queue(&bake_layout, &AURORA_SKYBOX_SHADER_HANDLE, "bake"),
+            filter_specular: queue(
+                &filter_layout,
+                &AURORA_ENVIRONMENT_SHADER_HANDLE,
+                "filter_specular",
+            ),
+            filter_diffuse: queue(
+                &filter_layout,
+                &AURORA_ENVIRONMENT_SHADER_HANDLE,
+                "filter_diffuse",
+            ),
+            bake_layout,
+            filter_layout,
+            filter_sampler,
+        }
+    }
+}
+
+/// Uniform buffers of each cubemap, kept from one frame to the next.
+#[derive(Resource, Default)]
+struct CubemapBakeBuffers(
+    HashMap<AssetId<Image>, (UniformBuffer<AuroraSettings>, UniformBuffer<AuroraPhases>)>,
+);
+
+/// A dispatch over every face of a cubemap, or of one of its mip levels.
+struct FaceDispatch {
+    bind_group: BindGroup,
+    size: u32,
+}
+
+/// Dispatches of this frame, in the order they run.
+#[derive(Resource, Default)]
+struct CubemapBakeBindGroups {
+    bakes: Vec<FaceDispatch>,
+    specular_filters: Vec<FaceDispatch>,
+    diffuse_filters: Vec<FaceDispatch>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_bind_groups(
+    bakes: Res<CubemapBakes>,
+    baked: Res<BakedEnvironments>,
+    pipelines: Res<CubemapBakePipelines>,
+    pipeline_cache: Res<PipelineCache>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut buffers: ResMut<CubemapBakeBuffers>,
+    mut bind_groups: ResMut<CubemapBakeBindGroups>,
+) {
+    let bind_groups = &mut *bind_groups;
+    bind_groups.bakes.clear();
+    bind_groups.specular_filters.clear();
+    bind_groups.diffuse_filters.clear();
+    buffers
+        .0
+        .retain(|id, _| bakes.0.iter().any(|bake| bake.image == *id));
+
+    // Nothing is drawn until the shaders have compiled
+    let ready = |id| pipeline_cache.get_compute_pipeline(id).is_some();
+    if !ready(pipelines.bake) {
+        return;
+    }
+    let filters_ready = ready(pipelines.filter_specular) && ready(pipelines.filter_diffuse);
+
+    for bake in &bakes.0 {
+        // Skip images that are not on the GPU yet
+        let Some(image) = images.get(bake.image) else {
+            continue;
+        };
+        let (settings, phases) = buffers.0.entry(bake.image).or_default();
+        settings.set(bake.settings);
+        settings.write_buffer(&render_device, &render_queue);
+        phases.set(bake.phases);
+        phases.write_buffer(&render_device, &render_queue);
+        let (Some(settings), Some(phases)) = (settings.binding(), phases.binding()) else {
+            continue;
+        };
+
+        let faces = faces_view(image, 0);
+        bind_groups.bakes.push(FaceDispatch {
+            bind_group: render_device.create_bind_group(
+                "aurora_cubemap_bake_bind_group",
+                &pipelines.bake_layout,
+                &BindGroupEntries::sequential((settings, phases, &faces)),
+            ),
+            size: image.size.x,
+        });
+
+        let Some(environment) = bake.environment.filter(|_| filters_ready) else {
+            continue;
+        };
+        let (Some(diffuse), Some(specular)) = (
+            images.get(environment.diffuse),
+            images.get(environment.specular),
+        ) else {
+            continue;
+        };
+        let filter = |target: &GpuImage, level: u32| FaceDispatch {
+            bind_group: render_device.create_bind_group(
+                "aurora_cubemap_filter_bind_group",
+                &pipelines.filter_layout,
+                &BindGroupEntries::sequential((
+                    &image.texture_view,
+                    &pipelines.filter_sampler,
+                    &faces_view(target, level),
+                )),
+            ),
+            size: (target.size.x >> level).max(1),
+        };
+        bind_groups
+            .specular_filters
+            .extend((0..specular.mip_level_count).map(|level| filter(specular, level)));
+        bind_groups.diffuse_filters.push(filter(diffuse, 0));
+        baked.insert(bake.image);
+    }
+}
+
+/// A view of every face of `image` at mip `level` that compute shaders can write to.
+fn faces_view(image: &GpuImage, level: u32) -> TextureView {
+    image.texture.create_view(&TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        base_mip_level: level,
+        mip_level_count: Some(1),
+        ..default()
+    })
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct CubemapBakeLabel;
+
+struct CubemapBakeNode;
+
+impl render_graph::Node for CubemapBakeNode {
+    fn run(
+        &self,
+        _graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let bind_groups = world.resource::<CubemapBakeBindGroups>();
+        if bind_groups.bakes.is_empty() {
+            return Ok(());
+        }
+        let pipelines = world.resource::<CubemapBakePipelines>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("aurora_cubemap_bake"),
+                    timestamp_writes: None,
+                });
+        // The filters read what the bakes wrote, the pass orders the dispatches
+        for (pipeline, dispatches) in [
+            (pipelines.bake, &bind_groups.bakes),
+            (pipelines.filter_specular, &bind_groups.specular_filters),
+            (pipelines.filter_diffuse, &bind_groups.diffuse_filters),
+        ] {
+            // Only dispatches of compiled pipelines were prepared
+            let Some(pipeline) = pipeline_cache.get_compute_pipeline(pipeline) else {
+                continue;
+            };
+            pass.set_pipeline(pipeline);
+            for dispatch in dispatches {
+                let groups = dispatch.size.div_ceil(WORKGROUP_SIZE);
+                pass.set_bind_group(0, &dispatch.bind_group, &[]);
+                pass.dispatch_workgroups(groups, groups, 6);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+//! Aurora curtains as folded ribbon meshes, a cheaper alternative to the volumetric mapping.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        mesh::{Indices, MeshVertexBufferLayoutRef, PrimitiveTopology},
+        primitives::Aabb,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::{
+    AuroraPhases, AuroraSettings, material::AuroraMaterial, settings::CurtainFolds,
+    shader::AURORA_CURTAIN_SHADER_HANDLE,
+};
+
+/// Hangs `count` aurora curtains side by side over this entity, as ribbons folded and rippled
+/// in the vertex shader by the same noise as the flat aurora, with ray streaks running up them.
+///
+/// The ribbons run along the entity's X axis, `spacing` apart along Z. They are spawned as
+/// children of this entity by the plugin and all share one mesh and one material, so they are
+/// drawn as a single instanced batch. Removing this component, or despawning the entity
+/// without its children, despawns them. Changing `count`, `length`, `height`, `altitude`,
+/// `spacing` or `segments` spawns new ribbons, the other fields only update the material.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[require(Transform, Visibility)]
+pub struct AuroraCurtains {
+    pub settings: AuroraSettings,
+    /// Number of curtains.
+    pub count: u32,
+    /// Length of each curtain, in world units.
+    pub length: f32,
+    /// Height of each curtain, in world units.
+    pub height: f32,
+    /// Height of the bottom edge of the curtains above this entity.
+    pub altitude: f32,
+    /// Distance between two neighbouring curtains.
+    pub spacing: f32,
+    /// How far the folds push the curtains sideways at a `waviness` of 1, in units of this
+    /// entity's space: scaling the entity scales the folds along with the curtains.
+    pub fold_amplitude: f32,
+    /// World units per unit of the fold noise, larger values give broader folds. Kept above
+    /// 0.001 by the shader.
+    pub fold_size: f32,
+    /// Number of segments along each curtain, enough for the folds to bend smoothly.
+    pub segments: u32,
+}
+
+impl AuroraCurtains {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// The fields the ribbons are built from.
+    fn layout(&self) -> (u32, f32, f32, f32, f32, u32) {
+        (
+            self.count,
+            self.length,
+            self.height,
+            self.altitude,
+            self.spacing,
+            self.segments,
+        )
+    }
+
+    fn folds(&self) -> CurtainFolds {
+        CurtainFolds {
+            amplitude: self.fold_amplitude,
+            size: self.fold_size,
+        }
+    }
+
+    /// Bounds of one ribbon, as folded by the vertex shader.
+    ///
+    /// The mesh itself is a flat sheet, so its own bounds would let the camera cull ribbons
+    /// that the folds bring into view. The large scale noise strays at most half of `waviness`
+    /// from the middle and the wave adds a tenth, times 1.3 at the top edge.
+    fn ribbon_aabb(&self) -> Aabb {
+        let fold = (0.5 * self.settings.waviness.abs() + 0.1) * self.fold_amplitude.abs() * 1.3;
+        Aabb::from_min_max(
+            Vec3::new(-0.5 * self.length, 0.0, -fold),
+            Vec3::new(0.5 * self.length, self.height, fold),
+        )
+    }
+}
+
+impl Default for AuroraCurtains {
+    fn default() -> Self {
+        Self {
+            settings: AuroraSettings::default(),
+            count: 5,
+            length: 120.0,
+            height: 30.0,
+            altitude: 40.0,
+            spacing: 15.0,
+            fold_amplitude: 12.0,
+            fold_size: 60.0,
+            segments: 96,
+        }
+    }
+}
+
+/// Material of the ribbons of an [`AuroraCurtains`], made and kept up to date by the plugin.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct CurtainMaterial {
+    #[uniform(0)]
+    settings: AuroraSettings,
+    #[uniform(1)]
+    phases: AuroraPhases,
+    #[uniform(2)]
+    folds: CurtainFolds,
+}
+
+impl Material for CurtainMaterial {
+    fn vertex_shader() -> ShaderRef {
+        AURORA_CURTAIN_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        AURORA_CURTAIN_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+        ])?];
+        // Curtains are seen from both sides
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
+
+impl AuroraMaterial for CurtainMaterial {
+    fn settings(&self) -> &AuroraSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut AuroraSettings {
+        &mut self.settings
+    }
+
+    fn phases(&self) -> &AuroraPhases {
+        &self.phases
+    }
+
+    fn phases_mut(&mut self) -> &mut AuroraPhases {
+        &mut self.phases
+    }
+}
+
+/// A flat `length` x `height` sheet along X, its bottom edge on the origin, split into
+/// `segments` along its length. UVs go along it and up it.
+fn ribbon_mesh(length: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(1);
+    let mut positions = Vec::with_capacity(2 * (segments as usize + 1));
+    let mut uvs = Vec::with_capacity(positions.capacity());
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let x = (u - 0.5) * length;
+        positions.extend([[x, 0.0, 0.0], [x, height, 0.0]]);
+        uvs.extend([[u, 0.0], [u, 1.0]]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let indices = (0..segments)
+        .flat_map(|segment| {
+            let i = segment * 2;
+            [i, i + 2, i + 1, i + 1, i + 2, i + 3]
+        })
+        .collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_indices(Indices::U32(indices))
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+}
+
+/// The ribbons the plugin spawned for an [`AuroraCurtains`].
+#[derive(Component)]
+pub(crate) struct CurtainRibbons {
+    material: Handle<CurtainMaterial>,
+    ribbons: Vec<Entity>,
+    layout: (u32, f32, f32, f32, f32, u32),
+}
+
+/// Despawns the ribbons of removed [`AuroraCurtains`], whether their entity still exists or not.
+pub(crate) fn despawn_curtains(
+    mut commands: Commands,
+    mut removed: RemovedComponents<AuroraCurtains>,
+    ribbons: Query<(Entity, &Parent), With<MeshMaterial3d<CurtainMaterial>>>,
+) {
+    for entity in removed.read() {
+        for (ribbon, _) in ribbons.iter().filter(|(_, parent)| parent.get() == entity) {
+            commands.entity(ribbon).despawn_recursive();
+        }
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<CurtainRibbons>();
+        }
+    }
+}
+
+/// Spawns the ribbons of new [`AuroraCurtains`], and respawns or updates them when they change.
+pub(crate) fn spawn_curtains(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CurtainMaterial>>,
+    curtains: Query<(Entity, &AuroraCurtains, Option<&CurtainRibbons>), Changed<AuroraCurtains>>,
+) {
+    for (entity, curtains, spawned) in &curtains {
+        if let Some(spawned) = spawned {
+            if spawned.layout == curtains.layout() {
+                if let Some(material) = materials.get_mut(&spawned.material) {
+                    material.settings = curtains.settings;
+                    material.folds = curtains.folds();
+                }
+                for &ribbon in &spawned.ribbons {
+                    commands.entity(ribbon).insert(curtains.ribbon_aabb());
+                }
+                continue;
+            }
+            for &ribbon in &spawned.ribbons {
+                commands.entity(ribbon).despawn_recursive();
+            }
+        }
+
+        let mesh = meshes.add(ribbon_mesh(
+            curtains.length,
+            curtains.height,
+            curtains.segments,
+        ));
+        let material = materials.add(CurtainMaterial {
+            settings: curtains.settings,
+            phases: AuroraPhases::new(0.0, &curtains.settings),
+            folds: curtains.folds(),
+        });
+        let middle = (curtains.count as f32 - 1.0) / 2.0;
+        let ribbons = (0..curtains.count)
+            .map(|i| {
+                // Staggered along their length so their ends don't line up
+                let stagger = ((i as f32 * 0.618_034).fract() - 0.5) * curtains.length * 0.25;
+                let z = (i as f32 - middle) * curtains.spacing;
+                commands
+                    .spawn((
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::from_xyz(stagger, curtains.altitude, z),
+                        curtains.ribbon_aabb(),
+                    ))
+                    .set_parent(entity)
+                    .id()
+            })
+            .collect();
+        commands.entity(entity).insert(CurtainRibbons {
+            material,
+            ribbons,
+            layout: curtains.layout(),
+        });
+    }
+}
@@ -0,0 +1,89 @@
+//! A hemisphere to put the aurora all around the viewer.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    math::{Vec2, Vec3},
+    render::mesh::{Indices, Mesh, MeshBuilder, PrimitiveTopology},
+};
+
+/// Builds an upper hemisphere centered on the origin, seen from the inside.
+///
+/// UVs are the azimuth, from 0 to 1 turning from `+Z` towards `+X`, and the elevation, from 0
+/// at the horizon to 1 at the zenith. This is what [`AuroraMapping::Dome`] expects.
+///
+/// [`AuroraMapping::Dome`]: crate::AuroraMapping::Dome
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyDome {
+    pub radius: f32,
+    /// Number of segments around the horizon.
+    pub sectors: u32,
+    /// Number of segments from the horizon to the zenith.
+    pub stacks: u32,
+}
+
+impl SkyDome {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SkyDome {
+    fn default() -> Self {
+        Self {
+            radius: 100.0,
+            sectors: 64,
+            stacks: 16,
+        }
+    }
+}
+
+impl MeshBuilder for SkyDome {
+    fn build(&self) -> Mesh {
+        let (sectors, stacks) = (self.sectors.max(3), self.stacks.max(1));
+        let vertex_count = ((sectors + 1) * (stacks + 1)) as usize;
+        let mut positions = Vec::with_capacity(vertex_count);
+        let mut normals = Vec::with_capacity(vertex_count);
+        let mut uvs = Vec::with_capacity(vertex_count);
+
+        // The first and last column share positions but not UVs, so the azimuth can wrap
+        for stack in 0..=stacks {
+            for sector in 0..=sectors {
+                let uv = Vec2::new(sector as f32 / sectors as f32, stack as f32 / stacks as f32);
+                let (azimuth, altitude) = (uv.x * TAU, uv.y * FRAC_PI_2);
+                let direction = Vec3::new(
+                    altitude.cos() * azimuth.sin(),
+                    altitude.sin(),
+                    altitude.cos() * azimuth.cos(),
+                );
+                positions.push((direction * self.radius).to_array());
+                normals.push((-direction).to_array());
+                uvs.push(uv.to_array());
+            }
+        }
+
+        let mut indices = Vec::with_capacity((sectors * stacks * 6) as usize);
+        for stack in 0..stacks {
+            for sector in 0..sectors {
+                let a = stack * (sectors + 1) + sector;
+                let (b, c) = (a + 1, a + sectors + 1);
+                let d = c + 1;
+                // Counter-clockwise seen from the center
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
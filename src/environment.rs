@@ -0,0 +1,150 @@
+//! The aurora as the light of the scene, through an environment map.
+
+use bevy::prelude::*;
+
+use crate::{
+    AuroraClock, AuroraPhases, AuroraSettings,
+    cubemap::{self, BakedEnvironments, CubemapBake, CubemapBakes, EnvironmentMaps},
+};
+
+/// Width and height of each face of the diffuse map, which only holds very blurry light.
+const DIFFUSE_RESOLUTION: u32 = 16;
+
+/// Lights the scene with the aurora through the [`EnvironmentMapLight`] of this entity, so
+/// shiny and rough surfaces alike pick up its colors.
+///
+/// The sky is baked into a small cubemap every `bake_interval` seconds, then filtered into the
+/// diffuse and specular maps on the GPU. It is also baked right away when this component
+/// changes. The cubemaps and the [`EnvironmentMapLight`] are created by the plugin, changing
+/// `resolution` makes new ones.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct AuroraEnvironmentMap {
+    pub settings: AuroraSettings,
+    /// Width and height of each face of the specular map, in pixels. Powers of two give every
+    /// roughness its own mip level.
+    pub resolution: u32,
+    /// Multiplier of the light, see [`EnvironmentMapLight::intensity`].
+    pub intensity: f32,
+    /// Real time between two bakes, in seconds. 0 bakes every frame.
+    pub bake_interval: f32,
+}
+
+impl AuroraEnvironmentMap {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for AuroraEnvironmentMap {
+    fn default() -> Self {
+        Self {
+            settings: AuroraSettings::default(),
+            resolution: 64,
+            intensity: 1000.0,
+            bake_interval: 1.0,
+        }
+    }
+}
+
+/// The cubemaps the plugin made for an [`AuroraEnvironmentMap`].
+#[derive(Component)]
+pub(crate) struct EnvironmentCubemaps {
+    /// The sky as baked, filtered into the maps of the [`EnvironmentMapLight`].
+    radiance: Handle<Image>,
+    specular: Handle<Image>,
+    resolution: u32,
+    /// Whether a bake was dispatched, until then one is queued every frame.
+    baked: bool,
+    /// Real time since the last bake.
+    since_bake: f32,
+}
+
+/// Makes sure every [`AuroraEnvironmentMap`] has an [`EnvironmentMapLight`] it can bake into,
+/// and queues a bake when one is due.
+#[allow(clippy::type_complexity)]
+pub(crate) fn bake_environment_maps(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    clock: Res<AuroraClock>,
+    mut images: ResMut<Assets<Image>>,
+    mut bakes: ResMut<CubemapBakes>,
+    baked: Res<BakedEnvironments>,
+    mut lights: Query<(
+        Entity,
+        Ref<AuroraEnvironmentMap>,
+        Option<(&mut EnvironmentMapLight, &mut EnvironmentCubemaps)>,
+    )>,
+) {
+    for (entity, aurora, current) in &mut lights {
+        let (radiance, maps) = match current {
+            Some((mut light, mut cubemaps))
+                if light.specular_map == cubemaps.specular
+                    && cubemaps.resolution == aurora.resolution =>
+            {
+                if light.intensity != aurora.intensity {
+                    light.intensity = aurora.intensity;
+                }
+                cubemaps.since_bake += time.delta_secs();
+                // The first bakes are dropped until the images are on the GPU and the
+                // pipelines have compiled, the interval only counts from one that went through
+                if baked.take(cubemaps.radiance.id()) && !cubemaps.baked {
+                    cubemaps.baked = true;
+                    cubemaps.since_bake = 0.0;
+                }
+                if cubemaps.baked
+                    && cubemaps.since_bake < aurora.bake_interval
+                    && !aurora.is_changed()
+                {
+                    continue;
+                }
+                cubemaps.since_bake = 0.0;
+                let maps = EnvironmentMaps {
+                    diffuse: light.diffuse_map.id(),
+                    specular: light.specular_map.id(),
+                };
+                (cubemaps.radiance.id(), maps)
+            }
+            _ => {
+                let resolution = aurora.resolution;
+                let radiance = images.add(cubemap::cubemap_image(resolution, 1));
+                let diffuse = images.add(cubemap::cubemap_image(DIFFUSE_RESOLUTION, 1));
+                let specular = images.add(cubemap::cubemap_image(
+                    resolution,
+                    cubemap::mip_level_count(resolution),
+                ));
+                let ids = (
+                    radiance.id(),
+                    EnvironmentMaps {
+                        diffuse: diffuse.id(),
+                        specular: specular.id(),
+                    },
+                );
+                commands.entity(entity).insert((
+                    EnvironmentMapLight {
+                        diffuse_map: diffuse,
+                        specular_map: specular.clone(),
+                        intensity: aurora.intensity,
+                        ..default()
+                    },
+                    EnvironmentCubemaps {
+                        radiance,
+                        specular,
+                        resolution: aurora.resolution,
+                        baked: false,
+                        since_bake: 0.0,
+                    },
+                ));
+                ids
+            }
+        };
+        bakes.0.push(CubemapBake {
+            image: radiance,
+            settings: aurora.settings,
+            phases: AuroraPhases::new(clock.elapsed(), &aurora.settings),
+            environment: Some(maps),
+        });
+    }
+}
@@ -0,0 +1,341 @@
+//! Writing rendered frames to disk, as still images, image sequences or animations.
+//!
+//! The format is picked from the output path:
+//! - a `.png` path containing a run of `#`, like `frames/aurora_####.png`, writes one PNG per
+//!   frame, the `#` being replaced by the zero padded frame number;
+//! - a `.gif` path writes an animated GIF;
+//! - a `.y4m` path writes an uncompressed YUV4MPEG2 video, and so does `-` but to stdout;
+//! - any other `.png` or `.apng` path writes an animated PNG, or a plain PNG for a single frame.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::math::Vec3;
+use image::{
+    DynamicImage, ImageError,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use thiserror::Error;
+
+use crate::render::Frame;
+
+/// Lowest frame rate an export can be timed at, one frame every 655.35 seconds: the longest
+/// delay a GIF can hold, in hundredths of a second in 16 bits.
+pub const MIN_FPS: f64 = 100.0 / 65535.0;
+
+/// Output formats supported by [`FrameWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One PNG file per frame.
+    PngSequence,
+    /// A single animated GIF. Frame delays are rounded to hundredths of a second.
+    Gif,
+    /// A single animated PNG.
+    Apng,
+    /// Uncompressed 8-bit Rec. 709 Y'CbCr 4:4:4 video, in limited range.
+    Y4m,
+}
+
+impl ExportFormat {
+    /// The format matching an output path, see the [module docs](self).
+    pub fn from_path(path: &str) -> Result<Self, ExportError> {
+        if path == "-" {
+            return Ok(Self::Y4m);
+        }
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("png") if path.contains('#') => Ok(Self::PngSequence),
+            _ if path.contains('#') => Err(ExportError::SequenceFormat(path.to_owned())),
+            Some("gif") => Ok(Self::Gif),
+            Some("png" | "apng") => Ok(Self::Apng),
+            Some("y4m") => Ok(Self::Y4m),
+            _ => Err(ExportError::UnknownFormat(path.to_owned())),
+        }
+    }
+}
+
+/// Errors while writing frames.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(
+        "cannot tell the output format of `{0}`, expected a .png, .apng, .gif or .y4m extension"
+    )]
+    UnknownFormat(String),
+    #[error(
+        "`{0}` has a `#` placeholder, which only numbers PNG sequences: expected a .png extension"
+    )]
+    SequenceFormat(String),
+    #[error("could not write `{path}`: {source}")]
+    Io { path: String, source: io::Error },
+    #[error("could not write `{path}`: {source}")]
+    Image { path: String, source: ImageError },
+    #[error("could not write `{path}`: {source}")]
+    Png {
+        path: String,
+        source: png::EncodingError,
+    },
+    #[error("invalid frame rate {0}, expected a number of at least {MIN_FPS}")]
+    FrameRate(f64),
+    #[error("expected a {expected:?} frame, got {actual:?}")]
+    FrameSize {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+}
+
+/// Writes a sequence of frames of the same size in one of the [`ExportFormat`]s.
+///
+/// Frames are timed by `fps` alone, so an export only depends on what is rendered and never on
+/// how long rendering takes. Call [`FrameWriter::finish`] after the last frame.
+pub struct FrameWriter {
+    path: String,
+    size: (u32, u32),
+    frame: u32,
+    encoder: Encoder,
+}
+
+enum Encoder {
+    PngSequence,
+    Gif(GifEncoder<BufWriter<File>>, image::Delay),
+    Apng(png::Writer<BufWriter<File>>),
+    Y4m(BufWriter<Box<dyn Write>>),
+}
+
+impl FrameWriter {
+    /// Prepares to write `frames` frames of `width` x `height` pixels, played at `fps`, which
+    /// must be finite and at least [`MIN_FPS`].
+    pub fn create(
+        path: &str,
+        width: u32,
+        height: u32,
+        frames: u32,
+        fps: f64,
+    ) -> Result<Self, ExportError> {
+        if !(fps.is_finite() && fps >= MIN_FPS) {
+            return Err(ExportError::FrameRate(fps));
+        }
+        let encoder = match ExportFormat::from_path(path)? {
+            ExportFormat::PngSequence => Encoder::PngSequence,
+            ExportFormat::Gif => {
+                let mut encoder = GifEncoder::new(create_file(path)?);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|source| image_error(path, source))?;
+                Encoder::Gif(encoder, gif_delay(fps))
+            }
+            ExportFormat::Apng => {
+                let mut encoder = png::Encoder::new(create_file(path)?, width, height);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                if frames > 1 {
+                    let (numerator, denominator) = frame_delay(fps);
+                    encoder
+                        .set_animated(frames, 0)
+                        .and_then(|()| encoder.set_frame_delay(numerator, denominator))
+                        .map_err(|source| png_error(path, source))?;
+                }
+                let writer = encoder
+                    .write_header()
+                    .map_err(|source| png_error(path, source))?;
+                Encoder::Apng(writer)
+            }
+            ExportFormat::Y4m => {
+                let output: Box<dyn Write> = if path == "-" {
+                    Box::new(io::stdout().lock())
+                } else {
+                    Box::new(File::create(path).map_err(|source| io_error(path, source))?)
+                };
+                let mut output = BufWriter::new(output);
+                let (numerator, denominator) = frame_rate(fps);
+                // `C444` without chroma subsampling, `A1:1` square pixels, `Ip` progressive
+                writeln!(
+                    output,
+                    "YUV4MPEG2 W{width} H{height} F{numerator}:{denominator} Ip A1:1 C444 XCOLORRANGE=LIMITED"
+                )
+                .map_err(|source| io_error(path, source))?;
+                Encoder::Y4m(output)
+            }
+        };
+        Ok(Self {
+            path: path.to_owned(),
+            size: (width, height),
+            frame: 0,
+            encoder,
+        })
+    }
+
+    /// Appends a frame, returns the path it was written to.
+    pub fn write(&mut self, frame: &Frame) -> Result<String, ExportError> {
+        if (frame.width, frame.height) != self.size {
+            return Err(ExportError::FrameSize {
+                expected: self.size,
+                actual: (frame.width, frame.height),
+            });
+        }
+        let path = match &mut self.encoder {
+            Encoder::PngSequence => {
+                let path = frame_path(&self.path, self.frame);
+                frame
+                    .to_rgb8()
+                    .save(&path)
+                    .map_err(|source| image_error(&path, source))?;
+                path
+            }
+            Encoder::Gif(encoder, delay) => {
+                let rgba = DynamicImage::ImageRgb8(frame.to_rgb8()).into_rgba8();
+                encoder
+                    .encode_frame(image::Frame::from_parts(rgba, 0, 0, *delay))
+                    .map_err(|source| image_error(&self.path, source))?;
+                self.path.clone()
+            }
+            Encoder::Apng(writer) => {
+                writer
+                    .write_image_data(frame.to_rgb8().as_raw())
+                    .map_err(|source| png_error(&self.path, source))?;
+                self.path.clone()
+            }
+            Encoder::Y4m(output) => {
+                write_y4m_frame(output, frame).map_err(|source| io_error(&self.path, source))?;
+                self.path.clone()
+            }
+        };
+        self.frame += 1;
+        Ok(path)
+    }
+
+    /// Completes the file, for formats that hold every frame.
+    pub fn finish(self) -> Result<(), ExportError> {
+        match self.encoder {
+            Encoder::PngSequence => Ok(()),
+            // The trailer is written when the encoder is dropped
+            Encoder::Gif(encoder, _) => {
+                drop(encoder);
+                Ok(())
+            }
+            Encoder::Apng(writer) => writer
+                .finish()
+                .map_err(|source| png_error(&self.path, source)),
+            Encoder::Y4m(mut output) => output
+                .flush()
+                .map_err(|source| io_error(&self.path, source)),
+        }
+    }
+}
+
+/// Replaces the first run of `#` in `pattern` with `frame`, zero padded to the run's length.
+pub fn frame_path(pattern: &str, frame: u32) -> String {
+    let Some(start) = pattern.find('#') else {
+        return pattern.to_owned();
+    };
+    let len = pattern[start..].chars().take_while(|&c| c == '#').count();
+    format!(
+        "{}{frame:0len$}{}",
+        &pattern[..start],
+        &pattern[start + len..]
+    )
+}
+
+/// GIF frame delay, rounded to the hundredth of a second it is stored in.
+fn gif_delay(fps: f64) -> image::Delay {
+    let centiseconds = (100.0 / fps).round().min(f64::from(u16::MAX)) as u32;
+    image::Delay::from_numer_denom_ms(centiseconds * 10, 1)
+}
+
+/// APNG frame delay as a fraction of a second: exact for whole frame rates, else in the finest
+/// of milliseconds, hundredths, tenths or seconds that fits in 16 bits.
+fn frame_delay(fps: f64) -> (u16, u16) {
+    if fps.fract() == 0.0 && fps <= f64::from(u16::MAX) {
+        return (1, fps as u16);
+    }
+    [1000, 100, 10, 1]
+        .into_iter()
+        .find_map(|denominator| {
+            let numerator = (f64::from(denominator) / fps).round();
+            (numerator <= f64::from(u16::MAX)).then_some((numerator.max(1.0) as u16, denominator))
+        })
+        .unwrap_or((u16::MAX, 1))
+}
+
+/// Y4M frame rate as a fraction: exact for whole frame rates, else to a thousandth of a frame.
+fn frame_rate(fps: f64) -> (u32, u32) {
+    if fps.fract() == 0.0 {
+        (fps as u32, 1)
+    } else {
+        ((fps * 1000.0).round() as u32, 1000)
+    }
+}
+
+/// Converts a linear sRGB color to 8-bit limited range Rec. 709 Y'CbCr.
+///
+/// Rec. 709 shares the sRGB primaries, so only the transfer function and the matrix differ.
+/// Out of gamut components are clipped first.
+pub fn linear_to_rec709_ycbcr(color: Vec3) -> [u8; 3] {
+    // Rec. 709 opto-electronic transfer function
+    let encode = |l: f32| {
+        let l = l.clamp(0.0, 1.0);
+        if l < 0.018 {
+            4.5 * l
+        } else {
+            1.099 * l.powf(0.45) - 0.099
+        }
+    };
+    let (r, g, b) = (encode(color.x), encode(color.y), encode(color.z));
+
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let cb = (b - y) / 1.8556;
+    let cr = (r - y) / 1.5748;
+
+    [
+        (16.0 + 219.0 * y).round() as u8,
+        (128.0 + 224.0 * cb).round() as u8,
+        (128.0 + 224.0 * cr).round() as u8,
+    ]
+}
+
+/// Writes a `FRAME` header followed by the Y', Cb and Cr planes.
+fn write_y4m_frame(output: &mut impl Write, frame: &Frame) -> io::Result<()> {
+    let pixels: Vec<[u8; 3]> = frame
+        .pixels
+        .iter()
+        .map(|&color| linear_to_rec709_ycbcr(color))
+        .collect();
+    output.write_all(b"FRAME\n")?;
+    for plane in 0..3 {
+        let samples: Vec<u8> = pixels.iter().map(|pixel| pixel[plane]).collect();
+        output.write_all(&samples)?;
+    }
+    Ok(())
+}
+
+fn create_file(path: &str) -> Result<BufWriter<File>, ExportError> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|source| io_error(path, source))
+}
+
+fn io_error(path: &str, source: io::Error) -> ExportError {
+    ExportError::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+fn image_error(path: &str, source: ImageError) -> ExportError {
+    ExportError::Image {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+fn png_error(path: &str, source: png::EncodingError) -> ExportError {
+    ExportError::Png {
+        path: path.to_owned(),
+        source,
+    }
+}
@@ -0,0 +1,109 @@
+//! Animated aurora borealis for Bevy.
+//!
+//! Add [`AuroraPlugin`] to your app, then spawn an [`AuroraSurfaceBundle`] (or any
+//! `Mesh3d` with a [`MeshMaterial3d<CustomMaterial>`]) to put the aurora on a surface, or an
+//! [`AuroraSurface2dBundle`] for 2D, or a [`MaterialNode<CustomUiMaterial>`] to fill a UI
+//! node. Add an [`AuroraSkybox`] or an [`AuroraBackground`] to a camera to make it the sky
+//! behind everything. An [`AuroraEnvironmentMap`] lights the scene with it, and
+//! [`AuroraCurtains`] hang folded curtains of it in the scene.
+
+use bevy::{
+    prelude::*,
+    sprite::{Material2dPlugin, MeshMaterial2d},
+    time::TimeSystem,
+};
+
+mod background;
+mod clock;
+pub mod cpu;
+mod cubemap;
+mod curtain;
+mod dome;
+mod environment;
+pub mod export;
+mod material;
+mod material2d;
+mod material_ui;
+mod phases;
+mod preset;
+pub mod render;
+mod settings;
+mod shader;
+mod skybox;
+
+pub use background::AuroraBackground;
+pub use clock::AuroraClock;
+pub use curtain::{AuroraCurtains, CurtainMaterial};
+pub use dome::SkyDome;
+pub use environment::AuroraEnvironmentMap;
+pub use material::{AuroraMapping, AuroraSurfaceBundle, CustomMaterial, CustomMaterialKey};
+pub use material_ui::CustomUiMaterial;
+pub use material2d::{AuroraSurface2dBundle, CustomMaterial2d};
+pub use phases::NOISE_PERIOD;
+pub use preset::{AuroraPreset, AuroraPresetError, AuroraPresetHandle, AuroraPresetLoader};
+pub use settings::{AuroraMappingSettings, AuroraPhases, AuroraSettings, CurtainFolds};
+pub use shader::{AURORA_CORE_SHADER_HANDLE, AURORA_SHADER_HANDLE};
+pub use skybox::AuroraSkybox;
+
+/// Registers the aurora material, the skybox and everything they need.
+///
+/// The shaders are embedded in the binary, so nothing has to be present in the assets directory.
+#[derive(Default)]
+pub struct AuroraPlugin {
+    /// Asset path of a material shader to use instead of the embedded one.
+    ///
+    /// Meant for development: point it at `shaders/animate_shader.wgsl` and enable the
+    /// `file_watcher` feature to hot reload the shaders while the app is running. The other
+    /// shaders are loaded from the same directory.
+    /// If a file cannot be loaded an error is logged and the embedded shader stays in use.
+    pub shader_override: Option<String>,
+}
+
+impl Plugin for AuroraPlugin {
+    fn build(&self, app: &mut App) {
+        shader::build(app, self.shader_override.as_deref());
+        cubemap::build(app);
+        background::build(app);
+        app.add_plugins((
+            MaterialPlugin::<CustomMaterial>::default(),
+            Material2dPlugin::<CustomMaterial2d>::default(),
+            UiMaterialPlugin::<CustomUiMaterial>::default(),
+            // Additive and animated in the vertex shader, so neither prepass nor shadows apply
+            MaterialPlugin::<CurtainMaterial> {
+                prepass_enabled: false,
+                shadows_enabled: false,
+                ..default()
+            },
+        ))
+        .init_asset::<AuroraPreset>()
+        .init_asset_loader::<AuroraPresetLoader>()
+        .init_resource::<AuroraClock>()
+        .add_systems(First, clock::advance_clock.after(TimeSystem))
+        .add_systems(
+            PostUpdate,
+            (
+                (
+                    preset::apply_presets::<CustomMaterial, MeshMaterial3d<CustomMaterial>>,
+                    preset::apply_presets::<CustomMaterial2d, MeshMaterial2d<CustomMaterial2d>>,
+                    preset::apply_presets::<CustomUiMaterial, MaterialNode<CustomUiMaterial>>,
+                    preset::apply_sky_presets,
+                ),
+                (
+                    clock::sync_materials::<CustomMaterial>,
+                    clock::sync_materials::<CustomMaterial2d>,
+                    clock::sync_materials::<CustomUiMaterial>,
+                    clock::sync_materials::<CurtainMaterial>,
+                    (curtain::despawn_curtains, curtain::spawn_curtains).chain(),
+                    skybox::bake_skyboxes,
+                    environment::bake_environment_maps,
+                ),
+            )
+                .chain(),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        cubemap::finish(app);
+        background::finish(app);
+    }
+}
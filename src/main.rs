@@ -1,46 +1,165 @@
-//! A shader that uses dynamic data like the time since startup.
-//! The time data is in the globals binding which is part of the `mesh_view_bindings` shader import.
+//! Demo of the aurora shader on a sky dome, lighting a chrome sphere.
+//!
+//! `aurora render --help` lists the options to render images without a window instead.
 
-use bevy::{
-    prelude::*,
-    reflect::TypePath,
-    render::render_resource::{AsBindGroup, ShaderRef},
+use aurora::{
+    AuroraClock, AuroraEnvironmentMap, AuroraPlugin, AuroraPresetHandle, AuroraSettings,
+    AuroraSurfaceBundle, CustomMaterial,
 };
+use bevy::prelude::*;
+use std::process::{ExitCode, Termination};
 
-/// This example uses a shader source file from the assets subdirectory
-const SHADER_ASSET_PATH: &str = "shaders/animate_shader.wgsl";
+mod cli;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {}
+        Some("render") => return cli::render(args),
+        Some(command) => {
+            eprintln!("error: unknown command `{command}`, expected `render` or nothing");
+            return ExitCode::FAILURE;
+        }
+    }
 
-fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, MaterialPlugin::<CustomMaterial>::default()))
+        .add_plugins((
+            DefaultPlugins,
+            AuroraPlugin {
+                // e.g. `AURORA_SHADER=shaders/animate_shader.wgsl cargo run --features file_watcher`
+                shader_override: std::env::var("AURORA_SHADER").ok(),
+            },
+        ))
         .add_systems(Startup, setup)
-        .run();
+        .add_systems(Update, (control_clock, show_clock).chain())
+        .run()
+        .report()
 }
 
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<CustomMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
 ) {
-    // cube
+    let settings = AuroraSettings {
+        // e.g. `AURORA_SEED=42 cargo run` for a different sky
+        seed: std::env::var("AURORA_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_default(),
+        ..default()
+    };
+    // e.g. `AURORA_PRESET=presets/crimson_storm.aurora.ron cargo run --features file_watcher`
+    let preset = std::env::var("AURORA_PRESET")
+        .ok()
+        .map(|path| AuroraPresetHandle(asset_server.load(path)));
+
+    // aurora, on a dome all around the camera
+    let mut aurora = commands.spawn(AuroraSurfaceBundle::dome(
+        &mut meshes,
+        &mut materials,
+        100.0,
+        settings,
+    ));
+    if let Some(preset) = preset.clone() {
+        aurora.insert(preset);
+    }
+
+    // camera, standing at the center of the dome and looking up at the sky, with the same
+    // aurora as its environment light
+    let mut camera = commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 0.0, 0.0).looking_to(Vec3::new(0.0, 0.6, -1.0), Vec3::Y),
+        AuroraEnvironmentMap::new(settings),
+    ));
+    if let Some(preset) = preset {
+        camera.insert(preset);
+    }
+
+    // a chrome sphere, lit by the aurora alone
     commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(10.0, 10.0))),
-        MeshMaterial3d(materials.add(CustomMaterial {})),
-        Transform::from_xyz(0.0, 0.5, 0.0),
+        Mesh3d(meshes.add(Sphere::new(1.5))),
+        MeshMaterial3d(standard_materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            metallic: 1.0,
+            perceptual_roughness: 0.15,
+            ..default()
+        })),
+        Transform::from_xyz(0.0, 2.0, -8.0),
     ));
 
-    // camera
+    // clock status and controls
     commands.spawn((
-        Camera3d::default(),
-        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Text::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
     ));
 }
 
-#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
-struct CustomMaterial {}
+/// Duration of a single step with the arrow keys, one frame at 60 fps.
+const FRAME_STEP: f64 = 1.0 / 60.0;
+
+fn control_clock(keys: Res<ButtonInput<KeyCode>>, mut clock: ResMut<AuroraClock>) {
+    if keys.just_pressed(KeyCode::Space) {
+        clock.toggle_pause();
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        let rate = clock.rate() + 0.5;
+        clock.set_rate(rate);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        let rate = clock.rate() - 0.5;
+        clock.set_rate(rate);
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        let rate = -clock.rate();
+        clock.set_rate(rate);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        clock.step(FRAME_STEP);
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        clock.step(-FRAME_STEP);
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        clock.set_elapsed(0.0);
+    }
+    // 1-9 jump to that many minutes into the animation
+    for (minutes, key) in [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if keys.just_pressed(key) {
+            clock.set_elapsed((minutes + 1) as f64 * 60.0);
+        }
+    }
+}
 
-impl Material for CustomMaterial {
-    fn fragment_shader() -> ShaderRef {
-        SHADER_ASSET_PATH.into()
+fn show_clock(clock: Res<AuroraClock>, mut text: Single<&mut Text>) {
+    if !clock.is_changed() {
+        return;
     }
+    text.0 = format!(
+        "t = {:.2}s  rate {:+.1}x{}\n\
+         Space: pause  Up/Down: rate  R: reverse  Left/Right: step  Home: restart  1-9: jump to minute",
+        clock.elapsed(),
+        clock.rate(),
+        if clock.is_paused() { "  (paused)" } else { "" },
+    );
 }
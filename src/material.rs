@@ -0,0 +1,224 @@
+//! The aurora material and helpers to put it on a mesh.
+
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::{
+    AuroraMappingSettings, AuroraPhases, AuroraSettings, SkyDome, shader::AURORA_SHADER_HANDLE,
+};
+
+/// How a fragment of the mesh is given a place in the sky.
+///
+/// The mappings reading world or screen positions ignore the UVs, so any number of meshes of
+/// any shape show one continuous sky.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AuroraMapping {
+    /// The UV square is the sky, `v` going up, widened by
+    /// [`AuroraMappingSettings::aspect`].
+    #[default]
+    Uv,
+    /// UVs are azimuth and elevation, like on a [`SkyDome`]. The aurora is projected onto a
+    /// layer overhead and the heights in [`AuroraSettings`] are elevations above the horizon,
+    /// as a fraction of the way to the zenith.
+    Dome,
+    /// The world X and Z of the fragment, scaled by [`AuroraMappingSettings::world_size`]:
+    /// horizontal tiles side by side line up. A plane of that size at the origin looks the
+    /// same as with [`AuroraMapping::Uv`].
+    WorldXz,
+    /// The sky seen through the surface from the camera, projected like
+    /// [`AuroraMapping::Dome`]. It stays in place as the surface moves, like a window.
+    WorldProjected,
+    /// The fragment's place in the viewport, the sky filling its height.
+    Screen,
+    /// Projections of the mesh's own space along its three axes, blended by the normal and
+    /// scaled by [`AuroraMappingSettings::object_size`]. Puts a seamless aurora on any mesh,
+    /// UVs or not, that moves along with it. Evaluates the aurora three times per fragment.
+    Triplanar,
+    /// Curtains ray marched through a layer of the world at altitude, seen from the camera
+    /// through the surface like with [`AuroraMapping::WorldProjected`]. They move with
+    /// parallax as the camera does. Quality and placement are set by the `volume_` fields of
+    /// [`AuroraMappingSettings`].
+    Volumetric,
+}
+
+impl AuroraMapping {
+    fn shader_def(self) -> Option<&'static str> {
+        match self {
+            Self::Uv => None,
+            Self::Dome => Some("AURORA_MAPPING_DOME"),
+            Self::WorldXz => Some("AURORA_MAPPING_WORLD_XZ"),
+            Self::WorldProjected => Some("AURORA_MAPPING_WORLD_PROJECTED"),
+            Self::Screen => Some("AURORA_MAPPING_SCREEN"),
+            Self::Triplanar => Some("AURORA_MAPPING_TRIPLANAR"),
+            Self::Volumetric => Some("AURORA_MAPPING_VOLUMETRIC"),
+        }
+    }
+}
+
+/// Aurora material, configured per instance through its [`AuroraSettings`] uniform.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+#[bind_group_data(CustomMaterialKey)]
+pub struct CustomMaterial {
+    #[uniform(0)]
+    pub settings: AuroraSettings,
+    /// Animation state, kept in sync with [`AuroraClock`](crate::AuroraClock) by the plugin.
+    #[uniform(1)]
+    pub phases: AuroraPhases,
+    #[uniform(2)]
+    pub mapping_settings: AuroraMappingSettings,
+    pub mapping: AuroraMapping,
+}
+
+impl CustomMaterial {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            phases: AuroraPhases::new(0.0, &settings),
+            mapping_settings: AuroraMappingSettings::default(),
+            mapping: AuroraMapping::default(),
+        }
+    }
+
+    pub fn with_mapping(mut self, mapping: AuroraMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn with_mapping_settings(mut self, mapping_settings: AuroraMappingSettings) -> Self {
+        self.mapping_settings = mapping_settings;
+        self
+    }
+}
+
+/// The parts of a [`CustomMaterial`] its pipeline is specialized on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomMaterialKey {
+    mapping: AuroraMapping,
+}
+
+impl From<&CustomMaterial> for CustomMaterialKey {
+    fn from(material: &CustomMaterial) -> Self {
+        Self {
+            mapping: material.mapping,
+        }
+    }
+}
+
+impl Material for CustomMaterial {
+    fn fragment_shader() -> ShaderRef {
+        AURORA_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let mapping = key.bind_group_data.mapping;
+        if let (Some(fragment), Some(def)) = (descriptor.fragment.as_mut(), mapping.shader_def()) {
+            fragment.shader_defs.push(def.into());
+        }
+        if mapping == AuroraMapping::Triplanar {
+            // The fragment needs the mesh's transform to get back to the mesh's own space
+            descriptor
+                .vertex
+                .shader_defs
+                .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment
+                    .shader_defs
+                    .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A material drawing the aurora, whose phases follow the [`AuroraClock`](crate::AuroraClock)
+/// and whose settings follow its [`AuroraPresetHandle`](crate::AuroraPresetHandle), if any.
+pub(crate) trait AuroraMaterial: Asset {
+    fn settings(&self) -> &AuroraSettings;
+    fn settings_mut(&mut self) -> &mut AuroraSettings;
+    fn phases(&self) -> &AuroraPhases;
+    fn phases_mut(&mut self) -> &mut AuroraPhases;
+}
+
+impl AuroraMaterial for CustomMaterial {
+    fn settings(&self) -> &AuroraSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut AuroraSettings {
+        &mut self.settings
+    }
+
+    fn phases(&self) -> &AuroraPhases {
+        &self.phases
+    }
+
+    fn phases_mut(&mut self) -> &mut AuroraPhases {
+        &mut self.phases
+    }
+}
+
+/// Everything needed to spawn a mesh covered by the aurora.
+#[derive(Bundle, Clone, Default)]
+pub struct AuroraSurfaceBundle {
+    pub mesh: Mesh3d,
+    pub material: MeshMaterial3d<CustomMaterial>,
+    pub transform: Transform,
+}
+
+impl AuroraSurfaceBundle {
+    /// A flat `width` x `depth` plane facing up, showing the aurora described by `settings`
+    /// at the plane's aspect ratio.
+    pub fn plane(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<CustomMaterial>,
+        width: f32,
+        depth: f32,
+        settings: AuroraSettings,
+    ) -> Self {
+        Self {
+            mesh: Mesh3d(meshes.add(Plane3d::default().mesh().size(width, depth))),
+            material: MeshMaterial3d(materials.add(
+                CustomMaterial::new(settings).with_mapping_settings(AuroraMappingSettings {
+                    aspect: width / depth,
+                    ..default()
+                }),
+            )),
+            transform: Transform::default(),
+        }
+    }
+
+    /// A [`SkyDome`] of the given `radius` centered on the origin, with the aurora all around.
+    pub fn dome(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<CustomMaterial>,
+        radius: f32,
+        settings: AuroraSettings,
+    ) -> Self {
+        Self {
+            mesh: Mesh3d(meshes.add(SkyDome::new(radius))),
+            material: MeshMaterial3d(
+                materials.add(CustomMaterial::new(settings).with_mapping(AuroraMapping::Dome)),
+            ),
+            transform: Transform::default(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+}
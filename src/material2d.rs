@@ -0,0 +1,91 @@
+//! The aurora material for 2D meshes, drawn by a `Camera2d`.
+
+use bevy::{
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, MeshMaterial2d},
+};
+
+use crate::{
+    AuroraPhases, AuroraSettings, material::AuroraMaterial, shader::AURORA_2D_SHADER_HANDLE,
+};
+
+/// 2D counterpart of [`CustomMaterial`](crate::CustomMaterial), sharing its look and settings.
+///
+/// The UV square is the sky with `v` going down, like on a [`Rectangle`] mesh, so the aurora
+/// hangs from the top of the mesh.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct CustomMaterial2d {
+    #[uniform(0)]
+    pub settings: AuroraSettings,
+    /// Animation state, kept in sync with [`AuroraClock`](crate::AuroraClock) by the plugin.
+    #[uniform(1)]
+    pub phases: AuroraPhases,
+}
+
+impl CustomMaterial2d {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            phases: AuroraPhases::new(0.0, &settings),
+        }
+    }
+}
+
+impl Material2d for CustomMaterial2d {
+    fn fragment_shader() -> ShaderRef {
+        AURORA_2D_SHADER_HANDLE.into()
+    }
+}
+
+impl AuroraMaterial for CustomMaterial2d {
+    fn settings(&self) -> &AuroraSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut AuroraSettings {
+        &mut self.settings
+    }
+
+    fn phases(&self) -> &AuroraPhases {
+        &self.phases
+    }
+
+    fn phases_mut(&mut self) -> &mut AuroraPhases {
+        &mut self.phases
+    }
+}
+
+/// Everything needed to spawn a 2D mesh covered by the aurora.
+///
+/// For a parallax background, move its transform with the camera at a fraction of the camera's
+/// speed and put it behind the rest of the scene with a negative `z`.
+#[derive(Bundle, Clone, Default)]
+pub struct AuroraSurface2dBundle {
+    pub mesh: Mesh2d,
+    pub material: MeshMaterial2d<CustomMaterial2d>,
+    pub transform: Transform,
+}
+
+impl AuroraSurface2dBundle {
+    /// A `width` x `height` rectangle, showing the aurora described by `settings`.
+    pub fn rectangle(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<CustomMaterial2d>,
+        width: f32,
+        height: f32,
+        settings: AuroraSettings,
+    ) -> Self {
+        Self {
+            mesh: Mesh2d(meshes.add(Rectangle::new(width, height))),
+            material: MeshMaterial2d(materials.add(CustomMaterial2d::new(settings))),
+            transform: Transform::default(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+}
@@ -0,0 +1,59 @@
+//! The aurora material for UI nodes.
+
+use bevy::{
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::{
+    AuroraPhases, AuroraSettings, material::AuroraMaterial, shader::AURORA_UI_SHADER_HANDLE,
+};
+
+/// UI counterpart of [`CustomMaterial`](crate::CustomMaterial), to fill a [`Node`] with the
+/// aurora through a [`MaterialNode`].
+///
+/// The height of the node spans the sky from bottom to top and its width shows more or less of
+/// the sky around the center depending on its aspect ratio, so resizing the node never
+/// stretches the aurora.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct CustomUiMaterial {
+    #[uniform(0)]
+    pub settings: AuroraSettings,
+    /// Animation state, kept in sync with [`AuroraClock`](crate::AuroraClock) by the plugin.
+    #[uniform(1)]
+    pub phases: AuroraPhases,
+}
+
+impl CustomUiMaterial {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            phases: AuroraPhases::new(0.0, &settings),
+        }
+    }
+}
+
+impl UiMaterial for CustomUiMaterial {
+    fn fragment_shader() -> ShaderRef {
+        AURORA_UI_SHADER_HANDLE.into()
+    }
+}
+
+impl AuroraMaterial for CustomUiMaterial {
+    fn settings(&self) -> &AuroraSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut AuroraSettings {
+        &mut self.settings
+    }
+
+    fn phases(&self) -> &AuroraPhases {
+        &self.phases
+    }
+
+    fn phases_mut(&mut self) -> &mut AuroraPhases {
+        &mut self.phases
+    }
+}
@@ -0,0 +1,174 @@
+//! Time-dependent terms of the shader, computed on the CPU in double precision.
+//!
+//! Feeding a raw, ever growing time to the shader makes every `sin(time * rate)` and noise
+//! offset lose precision as the app runs. Instead, each term is reduced here to a small range
+//! it is periodic over: oscillators to `[0, 2π)`, noise offsets to the period of the noise
+//! lattice and pulse cycles to their index modulo that period. The shader sees the exact same
+//! values after a minute or after a month.
+//!
+//! With [`AuroraSettings::loop_period`] set, every term is also made periodic over the loop, so
+//! that an animation rendered over exactly one period can be played in a seamless loop.
+
+use std::f64::consts::TAU;
+
+use bevy::math::{DVec2, Vec2, Vec4};
+
+use crate::{AuroraPhases, AuroraSettings};
+
+/// Period of the noise lattice: the shader's `hash` wraps integer cells modulo this.
+///
+/// Every noise lookup scales its offset by an integer, so wrapping offsets modulo the period
+/// leaves the picture unchanged. Kept small so the offsets stay precise after that scaling.
+pub const NOISE_PERIOD: u32 = 256;
+
+/// Rates of the shader's oscillators, in radians per unit of animation time, in the order
+/// they are packed in [`AuroraPhases::oscillators_a`], `oscillators_b` and `oscillators_c`.
+const OSCILLATOR_RATES: [f64; 12] = [
+    // wave, flow and curtains, surge pattern 1 and `t1`, surge pattern 2 and `t2`
+    1.0, 0.7, 0.3, 0.2, //
+    // surge pattern 3, `t3`, second layer wave, second layer flow
+    0.15, 0.4, 0.8, 0.5, //
+    // `t4`, rays, unused
+    0.25, 0.1, 0.0, 0.0,
+];
+
+/// Noise drift velocities that are not part of [`AuroraSettings`].
+const SMALL_DRIFT: DVec2 = DVec2::new(0.2, 0.0);
+const FINE_DRIFT: DVec2 = DVec2::new(0.3, 0.15);
+const MICRO_DRIFT: DVec2 = DVec2::new(0.4, -0.3);
+const CROSS_DRIFT: DVec2 = DVec2::new(0.15, -0.05);
+const SECOND_DETAIL_DRIFT: DVec2 = DVec2::new(-0.2, 0.1);
+const NEBULA_DRIFTS: [f64; 3] = [0.01, -0.02, 0.03];
+
+impl AuroraPhases {
+    /// Phases for `elapsed` seconds of [`AuroraClock`](crate::AuroraClock) time.
+    pub fn new(elapsed: f64, settings: &AuroraSettings) -> Self {
+        let timeline = Timeline::new(elapsed, settings);
+
+        let oscillators = |first: usize| {
+            Vec4::new(
+                timeline.oscillator(OSCILLATOR_RATES[first]),
+                timeline.oscillator(OSCILLATOR_RATES[first + 1]),
+                timeline.oscillator(OSCILLATOR_RATES[first + 2]),
+                timeline.oscillator(OSCILLATOR_RATES[first + 3]),
+            )
+        };
+        let drifts = |a: DVec2, b: DVec2| {
+            let (a, b) = (timeline.drift(a), timeline.drift(b));
+            Vec4::new(a.x, a.y, b.x, b.y)
+        };
+
+        let (surge_cycle, surge_progress) = timeline.cycle(settings.surge_frequency);
+        let (pulse_cycle, pulse_progress) = timeline.cycle(settings.pulse_frequency);
+
+        Self {
+            oscillators_a: oscillators(0),
+            oscillators_b: oscillators(4),
+            oscillators_c: oscillators(8),
+            cycles: Vec4::new(surge_cycle, surge_progress, pulse_cycle, pulse_progress),
+            drift_a: drifts(settings.drift.as_dvec2(), FINE_DRIFT),
+            drift_b: drifts(MICRO_DRIFT, CROSS_DRIFT),
+            drift_c: drifts(settings.second_drift.as_dvec2(), SECOND_DETAIL_DRIFT),
+            drift_d: Vec4::new(
+                timeline.drift_x(SMALL_DRIFT.x),
+                timeline.drift_x(NEBULA_DRIFTS[0]),
+                timeline.drift_x(NEBULA_DRIFTS[1]),
+                timeline.drift_x(NEBULA_DRIFTS[2]),
+            ),
+        }
+    }
+}
+
+/// Animation time, in the units the shader's rates are expressed in.
+enum Timeline {
+    /// Time flows on forever.
+    Linear(f64),
+    /// Time loops over `period`, and is currently `fraction` of the way through the loop.
+    ///
+    /// Oscillators and pulses are quantized to a whole number of cycles per loop, at least one
+    /// unless they are still, and drifts follow a closed path starting with their usual
+    /// velocity, so the end of the loop matches its start exactly.
+    Looping { period: f64, fraction: f64 },
+}
+
+impl Timeline {
+    fn new(elapsed: f64, settings: &AuroraSettings) -> Self {
+        let speed = f64::from(settings.speed);
+        if settings.loop_period <= 0.0 {
+            return Self::Linear(elapsed * speed);
+        }
+
+        let loop_period = f64::from(settings.loop_period);
+        let fraction = (elapsed / loop_period).rem_euclid(1.0);
+        // A frame time meant to land on a loop boundary may be a rounding error short of it,
+        // which would otherwise pick the last pulse of the loop instead of the first one
+        let fraction = if 1.0 - fraction < 1e-9 { 0.0 } else { fraction };
+        Self::Looping {
+            period: loop_period * speed,
+            fraction,
+        }
+    }
+
+    /// Phase in `[0, 2π)` of an oscillator turning at `rate` radians per unit of time.
+    fn oscillator(&self, rate: f64) -> f32 {
+        match *self {
+            Self::Linear(time) => (time * rate).rem_euclid(TAU) as f32,
+            Self::Looping { period, fraction } => {
+                let turns = whole_cycles(rate * period / TAU);
+                ((turns * fraction).rem_euclid(1.0) * TAU) as f32
+            }
+        }
+    }
+
+    /// Index, modulo [`NOISE_PERIOD`], and progress of a cycle repeating `frequency` times
+    /// per unit of time.
+    fn cycle(&self, frequency: f32) -> (f32, f32) {
+        let frequency = f64::from(frequency);
+        let cycles = match *self {
+            Self::Linear(time) => time * frequency,
+            Self::Looping { period, fraction } => whole_cycles(frequency * period) * fraction,
+        };
+        (
+            cycles.floor().rem_euclid(f64::from(NOISE_PERIOD)) as f32,
+            cycles.rem_euclid(1.0) as f32,
+        )
+    }
+
+    /// Offset of a noise layer drifting at `velocity`, modulo [`NOISE_PERIOD`].
+    ///
+    /// When looping, the offset goes around a circle instead of a straight line.
+    fn drift(&self, velocity: DVec2) -> Vec2 {
+        let offset = match *self {
+            Self::Linear(time) => velocity * time,
+            Self::Looping { period, fraction } => {
+                let (sin, cos) = (fraction * TAU).sin_cos();
+                let radius = period / TAU;
+                (velocity * sin + velocity.perp() * (1.0 - cos)) * radius
+            }
+        };
+        offset
+            .rem_euclid(DVec2::splat(f64::from(NOISE_PERIOD)))
+            .as_vec2()
+    }
+
+    /// Offset of a noise layer drifting horizontally at `velocity`, modulo [`NOISE_PERIOD`].
+    ///
+    /// When looping, the offset swings back and forth instead.
+    fn drift_x(&self, velocity: f64) -> f32 {
+        let offset = match *self {
+            Self::Linear(time) => velocity * time,
+            Self::Looping { period, fraction } => velocity * (fraction * TAU).sin() * period / TAU,
+        };
+        offset.rem_euclid(f64::from(NOISE_PERIOD)) as f32
+    }
+}
+
+/// `cycles` rounded to a whole number, keeping at least one cycle when it is not zero so that
+/// terms slower than the loop still move instead of freezing.
+fn whole_cycles(cycles: f64) -> f64 {
+    if cycles == 0.0 {
+        0.0
+    } else {
+        cycles.round().abs().max(1.0).copysign(cycles)
+    }
+}
@@ -0,0 +1,168 @@
+//! Aurora presets stored as `.aurora.ron` asset files.
+//!
+//! A preset is an [`AuroraSettings`] written in RON, fields that are left out keep their
+//! default value:
+//!
+//! ```ron
+//! (
+//!     speed: 0.3,
+//!     green: (0.85, -0.2, 0.15),
+//!     second_strength: 1.2,
+//! )
+//! ```
+//!
+//! Put an [`AuroraPresetHandle`] next to a [`MeshMaterial3d<CustomMaterial>`], a
+//! [`MeshMaterial2d<CustomMaterial2d>`], a [`MaterialNode<CustomUiMaterial>`], an
+//! [`AuroraBackground`], an [`AuroraSkybox`], an [`AuroraEnvironmentMap`] or some
+//! [`AuroraCurtains`] and the aurora follows the preset, including when the file is edited on
+//! disk with the `file_watcher` feature enabled.
+//!
+//! [`CustomMaterial`]: crate::CustomMaterial
+//! [`CustomMaterial2d`]: crate::CustomMaterial2d
+//! [`CustomUiMaterial`]: crate::CustomUiMaterial
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    AuroraBackground, AuroraCurtains, AuroraEnvironmentMap, AuroraSettings, AuroraSkybox,
+    material::AuroraMaterial,
+};
+
+/// A named set of [`AuroraSettings`], loaded from a `.aurora.ron` file.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct AuroraPreset {
+    pub settings: AuroraSettings,
+}
+
+impl AuroraPreset {
+    /// Parses a preset from the contents of a `.aurora.ron` file.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, AuroraPresetError> {
+        Ok(ron::de::from_bytes(bytes)?)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AuroraPresetError {
+    #[error("could not read aurora preset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse aurora preset: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct AuroraPresetLoader;
+
+impl AssetLoader for AuroraPresetLoader {
+    type Asset = AuroraPreset;
+    type Settings = ();
+    type Error = AuroraPresetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        AuroraPreset::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aurora.ron"]
+    }
+}
+
+/// Drives the aurora of this entity from an [`AuroraPreset`], see the [module docs](self).
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct AuroraPresetHandle(pub Handle<AuroraPreset>);
+
+/// Copies presets into the materials of type `M` using them through an `H` component when
+/// either side changes.
+pub(crate) fn apply_presets<M, H>(
+    mut events: EventReader<AssetEvent<AuroraPreset>>,
+    presets: Res<Assets<AuroraPreset>>,
+    mut materials: ResMut<Assets<M>>,
+    surfaces: Query<(Ref<AuroraPresetHandle>, &H)>,
+) where
+    M: AuroraMaterial,
+    H: Component + std::ops::Deref<Target = Handle<M>>,
+{
+    let updated = updated_presets(&mut events);
+
+    for (preset_handle, material_handle) in &surfaces {
+        let Some(preset) = due_preset(&presets, &updated, &preset_handle) else {
+            continue;
+        };
+        if let Some(material) = materials.get_mut(&**material_handle) {
+            *material.settings_mut() = preset.settings;
+        }
+    }
+}
+
+/// Copies presets into the skies, environment maps and curtains using them when either side
+/// changes.
+pub(crate) fn apply_sky_presets(
+    mut events: EventReader<AssetEvent<AuroraPreset>>,
+    presets: Res<Assets<AuroraPreset>>,
+    mut backgrounds: Query<(Ref<AuroraPresetHandle>, &mut AuroraBackground)>,
+    mut skyboxes: Query<(Ref<AuroraPresetHandle>, &mut AuroraSkybox)>,
+    mut environment_maps: Query<(Ref<AuroraPresetHandle>, &mut AuroraEnvironmentMap)>,
+    mut curtains: Query<(Ref<AuroraPresetHandle>, &mut AuroraCurtains)>,
+) {
+    let updated = updated_presets(&mut events);
+
+    for (preset_handle, mut background) in &mut backgrounds {
+        if let Some(preset) = due_preset(&presets, &updated, &preset_handle) {
+            background.settings = preset.settings;
+        }
+    }
+    for (preset_handle, mut skybox) in &mut skyboxes {
+        if let Some(preset) = due_preset(&presets, &updated, &preset_handle) {
+            skybox.settings = preset.settings;
+        }
+    }
+    for (preset_handle, mut environment_map) in &mut environment_maps {
+        if let Some(preset) = due_preset(&presets, &updated, &preset_handle) {
+            environment_map.settings = preset.settings;
+        }
+    }
+    for (preset_handle, mut curtains) in &mut curtains {
+        if let Some(preset) = due_preset(&presets, &updated, &preset_handle) {
+            curtains.settings = preset.settings;
+        }
+    }
+}
+
+/// Presets that finished loading or changed since last time.
+fn updated_presets(
+    events: &mut EventReader<AssetEvent<AuroraPreset>>,
+) -> HashSet<AssetId<AuroraPreset>> {
+    events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The preset to apply, if either side changed.
+fn due_preset<'a>(
+    presets: &'a Assets<AuroraPreset>,
+    updated: &HashSet<AssetId<AuroraPreset>>,
+    preset_handle: &Ref<AuroraPresetHandle>,
+) -> Option<&'a AuroraPreset> {
+    if preset_handle.is_changed() || updated.contains(&preset_handle.id()) {
+        presets.get(&preset_handle.0)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,79 @@
+//! Offline rendering of the aurora on the CPU, for machines without a GPU.
+//!
+//! Frames are evaluated with the [`cpu`](crate::cpu) reference implementation, with the UV
+//! square stretched over the whole image: `(0, 0)` is the bottom left corner.
+
+use std::thread;
+
+use bevy::{
+    color::{ColorToPacked, LinearRgba, Srgba},
+    math::{Vec2, Vec3},
+};
+use image::RgbImage;
+
+use crate::{AuroraPhases, AuroraSettings, cpu};
+
+/// A rendered image in linear sRGB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major pixels, starting with the top row.
+    pub pixels: Vec<Vec3>,
+}
+
+impl Frame {
+    pub fn pixel(&self, x: u32, y: u32) -> Vec3 {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Encodes the frame to 8-bit sRGB.
+    pub fn to_rgb8(&self) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let c = self.pixel(x, y);
+            image::Rgb(Srgba::from(LinearRgba::rgb(c.x, c.y, c.z)).to_u8_array_no_alpha())
+        })
+    }
+}
+
+/// Renders a `width` x `height` frame of the aurora at `time` seconds, using every core.
+pub fn render_frame(settings: &AuroraSettings, time: f64, width: u32, height: u32) -> Frame {
+    let mut pixels = vec![Vec3::ZERO; width as usize * height as usize];
+    if pixels.is_empty() {
+        return Frame {
+            width,
+            height,
+            pixels,
+        };
+    }
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let rows_per_chunk = (height as usize).div_ceil(threads);
+    let size = Vec2::new(width as f32, height as f32);
+    let phases = &AuroraPhases::new(time, settings);
+
+    thread::scope(|scope| {
+        for (chunk_index, chunk) in pixels
+            .chunks_mut(rows_per_chunk * width as usize)
+            .enumerate()
+        {
+            scope.spawn(move || {
+                let first_row = chunk_index * rows_per_chunk;
+                for (i, pixel) in chunk.iter_mut().enumerate() {
+                    let x = i % width as usize;
+                    let y = first_row + i / width as usize;
+                    let uv = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / size;
+                    *pixel =
+                        cpu::fragment_with_phases(Vec2::new(uv.x, 1.0 - uv.y), phases, settings)
+                            .truncate();
+                }
+            });
+        }
+    });
+
+    Frame {
+        width,
+        height,
+        pixels,
+    }
+}
@@ -0,0 +1,253 @@
+//! Uniform parameters shared by the aurora shader and the Rust side.
+
+use bevy::math::{Vec2, Vec3};
+
+pub use uniforms::{AuroraMappingSettings, AuroraPhases, AuroraSettings, CurtainFolds};
+
+// The `ShaderType` derive emits a never called layout check per field as a free function next
+// to the struct, out of reach of an attribute on the struct itself. The uniform structs get a
+// module of their own so that `dead_code` is allowed for their definitions alone.
+#[allow(dead_code)]
+mod uniforms {
+    use bevy::{
+        math::{Vec2, Vec3, Vec4},
+        reflect::Reflect,
+        render::render_resource::ShaderType,
+    };
+    use serde::{Deserialize, Serialize};
+
+    /// Tunable parameters of the aurora shader, uploaded as a uniform block.
+    ///
+    /// The field order must match the `AuroraSettings` struct in the shader.
+    /// Colors are in OKLab (`L`, `a`, `b`), which is also the space they are blended in.
+    ///
+    /// The same struct is what `.aurora.ron` presets deserialize into, any field left out of a
+    /// preset keeps its default value.
+    #[derive(ShaderType, Reflect, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+    #[serde(default)]
+    pub struct AuroraSettings {
+        /// Multiplier applied to the [`AuroraClock`](crate::AuroraClock) time before anything is
+        /// animated.
+        pub speed: f32,
+        /// Period after which the animation repeats exactly, in seconds of clock time, or `0` for
+        /// an animation that never repeats. Only read on the CPU, where it shapes the
+        /// [`AuroraPhases`].
+        ///
+        /// It is still uploaded with the rest, as the `ShaderType` derive cannot leave a field out,
+        /// and it belongs here so presets and every material carry it along with the look it loops.
+        /// The shader declares it to keep the layouts identical and never reads it.
+        ///
+        /// [`AuroraPhases`]: crate::AuroraPhases
+        pub loop_period: f32,
+        /// Vertical stretch of the noise field, higher values give thinner bands.
+        pub y_stretch: f32,
+        /// How strongly the large scale noise bends the aurora bands.
+        pub waviness: f32,
+        /// Cadence of the brightness surges, in cycles per unit of animation time.
+        pub surge_frequency: f32,
+        /// Cadence of the curtain and ray pulses, in cycles per unit of animation time.
+        pub pulse_frequency: f32,
+        /// Scale applied to the aurora intensity when blending it over the sky.
+        pub blend_strength: f32,
+        /// Upper bound of the aurora blend factor.
+        pub blend_cap: f32,
+        /// Output alpha where there is no aurora.
+        pub alpha_base: f32,
+        /// Additional output alpha at full aurora intensity.
+        pub alpha_scale: f32,
+        /// Seed of the noise, each seed gives a different but reproducible sky.
+        pub seed: u32,
+
+        /// Main layer color, blended with `teal` over time.
+        pub green: Vec3,
+        /// Main layer color that `green` turns into and back over time.
+        pub teal: Vec3,
+        /// Main layer color away from the center, blended with `purple` over time.
+        pub blue: Vec3,
+        /// Main layer color away from the center that `blue` turns into and back over time.
+        pub purple: Vec3,
+        /// Second, higher layer color, blended with `pink` over time.
+        pub red: Vec3,
+        /// Second layer color that `red` turns into and back over time.
+        pub pink: Vec3,
+
+        /// Drift of the main layer noise, in noise units per unit of animation time.
+        pub drift: Vec2,
+        /// Drift of the second layer noise, in noise units per unit of animation time.
+        pub second_drift: Vec2,
+        /// Vertical center of the main layer, in UV space.
+        pub height: f32,
+        /// How quickly the main layer fades away from its center, lower is sharper.
+        pub height_falloff: f32,
+        /// Vertical center of the second layer, in UV space.
+        pub second_height: f32,
+        /// How quickly the second layer fades away from its center, lower is sharper.
+        pub second_height_falloff: f32,
+
+        /// Strength of the soft glow around the main layer.
+        pub glow_strength: f32,
+        /// Strength of the fine wisps in the main layer.
+        pub wisp_strength: f32,
+        /// Strength of the second layer.
+        pub second_strength: f32,
+        /// Strength of the brightness surges.
+        pub surge_strength: f32,
+        /// Strength of the pulsing vertical curtains.
+        pub curtain_strength: f32,
+        /// Strength of the pulsing vertical rays.
+        pub ray_strength: f32,
+        /// Strength of the background space dust.
+        pub nebula_strength: f32,
+        /// Blend from the colors above (0) to a physically inspired emission model (1).
+        ///
+        /// The model colors the aurora by altitude through the main layer, with the spectral
+        /// lines real aurorae glow in converted through CIE 1931 to linear sRGB: the 557.7 nm
+        /// oxygen green low down, the 630 nm oxygen red high up and the 427.8 nm nitrogen blue
+        /// along the lower edge and at the very top.
+        pub physical_emission: f32,
+    }
+
+    /// Parameters of the [`AuroraMapping`] of a [`CustomMaterial`], uploaded as a uniform block.
+    ///
+    /// The field order must match the `AuroraMappingSettings` struct in the shader.
+    ///
+    /// [`AuroraMapping`]: crate::AuroraMapping
+    /// [`CustomMaterial`]: crate::CustomMaterial
+    #[derive(ShaderType, Reflect, Debug, Clone, Copy, PartialEq)]
+    pub struct AuroraMappingSettings {
+        /// Width over height of the surface, for [`AuroraMapping::Uv`]. The sky is widened around
+        /// its middle by this factor, so the patterns keep their proportions on a surface that
+        /// isn't square.
+        ///
+        /// [`AuroraMapping::Uv`]: crate::AuroraMapping::Uv
+        pub aspect: f32,
+        /// World units spanned by the sky along X and Z, for [`AuroraMapping::WorldXz`].
+        ///
+        /// [`AuroraMapping::WorldXz`]: crate::AuroraMapping::WorldXz
+        pub world_size: f32,
+        /// World X and Z of the middle of the sky, for [`AuroraMapping::WorldXz`], and of the
+        /// curtains for [`AuroraMapping::Volumetric`].
+        ///
+        /// [`AuroraMapping::WorldXz`]: crate::AuroraMapping::WorldXz
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub world_origin: Vec2,
+        /// Units of the mesh's own space spanned by the sky on each face, for
+        /// [`AuroraMapping::Triplanar`].
+        ///
+        /// [`AuroraMapping::Triplanar`]: crate::AuroraMapping::Triplanar
+        pub object_size: f32,
+        /// Samples taken along each ray, for [`AuroraMapping::Volumetric`]. More is smoother and
+        /// slower, the shader takes at most 256.
+        ///
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub volume_steps: u32,
+        /// Farthest distance from the camera the curtains are sampled at, in world units, for
+        /// [`AuroraMapping::Volumetric`]. They fade out on the way there.
+        ///
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub volume_distance: f32,
+        /// World Y of the bottom edge of the curtains, for [`AuroraMapping::Volumetric`].
+        ///
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub volume_altitude: f32,
+        /// Height of the curtains in world units, for [`AuroraMapping::Volumetric`].
+        ///
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub volume_thickness: f32,
+        /// World units spanned by the curtain pattern along X and Z, for
+        /// [`AuroraMapping::Volumetric`]. Neighbouring curtains are about 40% of it apart.
+        ///
+        /// [`AuroraMapping::Volumetric`]: crate::AuroraMapping::Volumetric
+        pub volume_size: f32,
+    }
+
+    /// Time-dependent terms of the shader, uploaded as a second uniform block.
+    ///
+    /// The field order must match the `AuroraPhases` struct in the shader.
+    #[derive(ShaderType, Reflect, Debug, Clone, Copy, PartialEq, Default)]
+    pub struct AuroraPhases {
+        /// Oscillator phases in radians, see `OSCILLATOR_RATES` in `phases.rs`.
+        pub oscillators_a: Vec4,
+        pub oscillators_b: Vec4,
+        pub oscillators_c: Vec4,
+        /// Surge cycle index and progress through the cycle, then the same for pulses.
+        pub cycles: Vec4,
+        /// Main layer offset, fine detail offset.
+        pub drift_a: Vec4,
+        /// Micro detail offset, cross detail offset.
+        pub drift_b: Vec4,
+        /// Second layer offset, second layer detail offset.
+        pub drift_c: Vec4,
+        /// Small detail horizontal offset, then the three horizontal nebula offsets.
+        pub drift_d: Vec4,
+    }
+
+    /// Fold parameters of the vertex shader of the [`AuroraCurtains`](crate::AuroraCurtains)
+    /// ribbons.
+    ///
+    /// The field order must match the `CurtainFolds` struct in the shader.
+    #[derive(ShaderType, Reflect, Debug, Clone, Copy, Default, PartialEq)]
+    pub struct CurtainFolds {
+        /// See [`AuroraCurtains::fold_amplitude`](crate::AuroraCurtains::fold_amplitude).
+        pub amplitude: f32,
+        /// See [`AuroraCurtains::fold_size`](crate::AuroraCurtains::fold_size).
+        pub size: f32,
+    }
+}
+
+impl Default for AuroraSettings {
+    fn default() -> Self {
+        Self {
+            speed: 0.2,
+            loop_period: 0.0,
+            y_stretch: 3.0,
+            waviness: 0.8,
+            surge_frequency: 0.03,
+            pulse_frequency: 0.04,
+            blend_strength: 0.7,
+            blend_cap: 0.8,
+            alpha_base: 0.1,
+            alpha_scale: 0.9,
+            seed: 0,
+
+            green: Vec3::new(0.86644, -0.233887, 0.179498),
+            teal: Vec3::new(0.7, -0.1, 0.1),
+            blue: Vec3::new(0.701674, 0.174566, -0.269156),
+            purple: Vec3::new(0.7, 0.3, -0.1),
+            red: Vec3::new(0.75, 0.35, 0.15),
+            pink: Vec3::new(0.78, 0.27, 0.05),
+
+            drift: Vec2::new(0.1, 0.05),
+            second_drift: Vec2::new(0.08, 0.02),
+            height: 0.5,
+            height_falloff: 0.7,
+            second_height: 0.6,
+            second_height_falloff: 0.6,
+
+            glow_strength: 0.4,
+            wisp_strength: 0.15,
+            second_strength: 0.7,
+            surge_strength: 0.12,
+            curtain_strength: 0.03,
+            ray_strength: 0.04,
+            nebula_strength: 0.15,
+            physical_emission: 0.0,
+        }
+    }
+}
+
+impl Default for AuroraMappingSettings {
+    fn default() -> Self {
+        Self {
+            aspect: 1.0,
+            world_size: 10.0,
+            world_origin: Vec2::ZERO,
+            object_size: 2.0,
+            volume_steps: 48,
+            volume_distance: 400.0,
+            volume_altitude: 40.0,
+            volume_thickness: 60.0,
+            volume_size: 200.0,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Embedding of the aurora shaders, with an optional on-disk override for development.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, load_internal_asset},
+    prelude::*,
+};
+
+/// Handle of the shader used by [`CustomMaterial`](crate::CustomMaterial).
+///
+/// It starts out holding the shader embedded in the binary and is replaced by the
+/// override shader, if any, whenever that one is (re)loaded.
+pub const AURORA_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x5f0b_9c1e_73a4_4d2b_8e61_2c4f_a0d3_9b17);
+
+/// Handle of the `aurora::core` shader module, imported by every aurora shader.
+pub const AURORA_CORE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x2d47_81b3_c6e9_4f05_9a3c_7e18_d5b2_640a);
+
+/// Handle of the compute shader baking the aurora into cubemaps.
+pub(crate) const AURORA_SKYBOX_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x91c3_0e5a_4b7d_4e62_b8f1_3a6c_2d90_e71f);
+
+/// Handle of the compute shader filtering baked cubemaps into environment maps.
+pub(crate) const AURORA_ENVIRONMENT_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x6a0e_f2c8_1d93_47b5_a47e_5c21_8b3f_d906);
+
+/// Handle of the shader used by [`CustomMaterial2d`](crate::CustomMaterial2d).
+pub(crate) const AURORA_2D_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x3e95_d0a7_6c12_4b8f_9d24_f1b6_07c3_5ae8);
+
+/// Handle of the shader used by [`CustomUiMaterial`](crate::CustomUiMaterial).
+pub(crate) const AURORA_UI_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x8d1f_46e2_b5a0_4c73_8e9b_2a64_f07d_c315);
+
+/// Handle of the shader folding and shading the ribbons of
+/// [`AuroraCurtains`](crate::AuroraCurtains).
+pub(crate) const AURORA_CURTAIN_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x57ae_0c93_d2f4_4b18_a6e0_83c5_1b7f_29d4);
+
+/// Handle of the shader drawing the aurora as a full-screen background.
+pub(crate) const AURORA_BACKGROUND_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0xc4b8_3f71_0e26_4a9d_b35c_97e0_1f4a_82d3);
+
+/// Embedded shaders and the file names they are overridden from.
+const EMBEDDED: [(Handle<Shader>, &str); 8] = [
+    (AURORA_SHADER_HANDLE, "animate_shader.wgsl"),
+    (AURORA_CORE_SHADER_HANDLE, "aurora_core.wgsl"),
+    (AURORA_SKYBOX_SHADER_HANDLE, "aurora_skybox.wgsl"),
+    (AURORA_ENVIRONMENT_SHADER_HANDLE, "aurora_environment.wgsl"),
+    (AURORA_BACKGROUND_SHADER_HANDLE, "aurora_background.wgsl"),
+    (AURORA_2D_SHADER_HANDLE, "aurora_2d.wgsl"),
+    (AURORA_UI_SHADER_HANDLE, "aurora_ui.wgsl"),
+    (AURORA_CURTAIN_SHADER_HANDLE, "aurora_curtain.wgsl"),
+];
+
+pub(crate) fn build(app: &mut App, override_path: Option<&str>) {
+    load_internal_asset!(
+        app,
+        AURORA_SHADER_HANDLE,
+        "../assets/shaders/animate_shader.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_CORE_SHADER_HANDLE,
+        "../assets/shaders/aurora_core.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_SKYBOX_SHADER_HANDLE,
+        "../assets/shaders/aurora_skybox.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_ENVIRONMENT_SHADER_HANDLE,
+        "../assets/shaders/aurora_environment.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_BACKGROUND_SHADER_HANDLE,
+        "../assets/shaders/aurora_background.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_2D_SHADER_HANDLE,
+        "../assets/shaders/aurora_2d.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_UI_SHADER_HANDLE,
+        "../assets/shaders/aurora_ui.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        AURORA_CURTAIN_SHADER_HANDLE,
+        "../assets/shaders/aurora_curtain.wgsl",
+        Shader::from_wgsl
+    );
+
+    if let Some(path) = override_path {
+        // Loaded at startup, once the asset server exists whatever order the plugins were added in
+        app.insert_resource(ShaderOverride {
+            path: path.into(),
+            overrides: Vec::new(),
+        })
+        .add_systems(Startup, load_shader_override)
+        .add_systems(
+            Update,
+            (apply_shader_override, report_shader_override_failure),
+        );
+    }
+}
+
+/// The shaders loaded from the path given in
+/// [`AuroraPlugin::shader_override`](crate::AuroraPlugin::shader_override) and its directory,
+/// with the embedded shader each one replaces.
+#[derive(Resource)]
+struct ShaderOverride {
+    path: PathBuf,
+    overrides: Vec<(Handle<Shader>, Handle<Shader>)>,
+}
+
+/// Starts loading the override shaders.
+fn load_shader_override(
+    mut shader_override: ResMut<ShaderOverride>,
+    asset_server: Res<AssetServer>,
+) {
+    // The other shaders are expected next to the material one, under their usual names
+    let path = shader_override.path.clone();
+    let directory = path.parent().unwrap_or(Path::new(""));
+    shader_override.overrides = EMBEDDED
+        .iter()
+        .map(|(embedded, name)| {
+            let path = if *embedded == AURORA_SHADER_HANDLE {
+                path.clone()
+            } else {
+                directory.join(name)
+            };
+            (asset_server.load(path), embedded.clone_weak())
+        })
+        .collect();
+}
+
+/// Copies each override shader over the embedded one every time it finishes (re)loading.
+fn apply_shader_override(
+    shader_override: Res<ShaderOverride>,
+    mut events: EventReader<AssetEvent<Shader>>,
+    mut shaders: ResMut<Assets<Shader>>,
+) {
+    for event in events.read() {
+        for (loaded, embedded) in &shader_override.overrides {
+            if !event.is_loaded_with_dependencies(loaded) && !event.is_modified(loaded) {
+                continue;
+            }
+            if let Some(shader) = shaders.get(loaded).cloned() {
+                info!("Using aurora shader override `{}`", shader.path);
+                shaders.insert(embedded, shader);
+            }
+        }
+    }
+}
+
+fn report_shader_override_failure(
+    shader_override: Res<ShaderOverride>,
+    mut events: EventReader<AssetLoadFailedEvent<Shader>>,
+) {
+    for event in events.read() {
+        if shader_override
+            .overrides
+            .iter()
+            .any(|(loaded, _)| event.id == loaded.id())
+        {
+            error!(
+                "Failed to load aurora shader override `{}`, keeping the embedded shader: {}",
+                event.path, event.error
+            );
+        }
+    }
+}
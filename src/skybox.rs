@@ -0,0 +1,103 @@
+//! The aurora as the sky of a camera, behind everything else.
+
+use bevy::{core_pipeline::Skybox, prelude::*};
+
+use crate::{
+    AuroraClock, AuroraPhases, AuroraSettings,
+    cubemap::{self, CubemapBake, CubemapBakes},
+};
+
+/// Renders the aurora into a cubemap every frame and shows it as the [`Skybox`] of this camera.
+///
+/// The sky is evaluated per direction, projected like [`AuroraMapping::Dome`] with the ground
+/// below the horizon. The cubemap and the [`Skybox`] are created by the plugin, changing
+/// `resolution` makes a new cubemap.
+///
+/// [`AuroraMapping::Dome`]: crate::AuroraMapping::Dome
+#[derive(Component, Debug, Clone, PartialEq)]
+#[require(Camera3d)]
+pub struct AuroraSkybox {
+    pub settings: AuroraSettings,
+    /// Width and height of each cubemap face, in pixels.
+    pub resolution: u32,
+    /// Multiplier of the sky color, see [`Skybox::brightness`].
+    pub brightness: f32,
+}
+
+impl AuroraSkybox {
+    pub fn new(settings: AuroraSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for AuroraSkybox {
+    fn default() -> Self {
+        Self {
+            settings: AuroraSettings::default(),
+            resolution: 512,
+            brightness: 1000.0,
+        }
+    }
+}
+
+/// The cubemap the plugin made for an [`AuroraSkybox`].
+///
+/// Cubemaps only live in the render world once uploaded, so what they were made for is kept here.
+#[derive(Component)]
+pub(crate) struct SkyboxCubemap {
+    image: Handle<Image>,
+    resolution: u32,
+}
+
+/// Makes sure every [`AuroraSkybox`] camera has a [`Skybox`] it can bake into, and queues the
+/// bake of this frame.
+pub(crate) fn bake_skyboxes(
+    mut commands: Commands,
+    clock: Res<AuroraClock>,
+    mut images: ResMut<Assets<Image>>,
+    mut bakes: ResMut<CubemapBakes>,
+    mut cameras: Query<(
+        Entity,
+        &AuroraSkybox,
+        Option<&mut Skybox>,
+        Option<&SkyboxCubemap>,
+    )>,
+) {
+    for (entity, aurora, skybox, cubemap) in &mut cameras {
+        let image = match (skybox, cubemap) {
+            (Some(mut skybox), Some(cubemap))
+                if skybox.image == cubemap.image && cubemap.resolution == aurora.resolution =>
+            {
+                if skybox.brightness != aurora.brightness {
+                    skybox.brightness = aurora.brightness;
+                }
+                cubemap.image.id()
+            }
+            _ => {
+                let image = images.add(cubemap::cubemap_image(aurora.resolution, 1));
+                let id = image.id();
+                commands.entity(entity).insert((
+                    Skybox {
+                        image: image.clone(),
+                        brightness: aurora.brightness,
+                        ..default()
+                    },
+                    SkyboxCubemap {
+                        image,
+                        resolution: aurora.resolution,
+                    },
+                ));
+                id
+            }
+        };
+        bakes.0.push(CubemapBake {
+            image,
+            settings: aurora.settings,
+            phases: AuroraPhases::new(clock.elapsed(), &aurora.settings),
+            environment: None,
+        });
+    }
+}
@@ -0,0 +1,58 @@
+//! The `render` subcommand of the demo binary, run as a user would.
+
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn out_path(name: &str) -> String {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn render(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aurora"))
+        .arg("render")
+        .args(["--size", "4x2"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_rejected(args: &[&str], message: &str) {
+    let output = render(args);
+    assert!(!output.status.success(), "{args:?} was accepted");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(message), "{args:?}: {stderr}");
+}
+
+#[test]
+fn zero_frames_are_rejected() {
+    let out = out_path("cli_test_zero.gif");
+    assert_rejected(
+        &["--frames", "0", "--out", &out],
+        "`--frames` must be at least 1",
+    );
+}
+
+#[test]
+fn frame_rates_that_cannot_be_stored_are_rejected() {
+    let out = out_path("cli_test_fps.gif");
+    for fps in ["0", "-30", "0.00001", "NaN", "inf"] {
+        assert_rejected(&["--fps", fps, "--out", &out], "`--fps` must be at least");
+    }
+}
+
+#[test]
+fn a_short_duration_still_renders_a_frame() {
+    let out = out_path("cli_test_short_#.png");
+    let (first, second) = (out.replace('#', "0"), out.replace('#', "1"));
+    let _ = std::fs::remove_file(&second);
+    let output = render(&["--duration", "0.01", "--fps", "30", "--out", &out]);
+    assert!(output.status.success(), "{output:?}");
+
+    assert!(PathBuf::from(first).exists());
+    assert!(!PathBuf::from(second).exists());
+}
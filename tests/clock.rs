@@ -0,0 +1,84 @@
+//! Playback of `AuroraClock` and the phases it writes into the materials.
+
+use aurora::{AuroraClock, AuroraPhases, AuroraPlugin, AuroraSettings, CustomMaterial};
+use bevy::prelude::*;
+
+#[test]
+fn ticks_at_the_rate() {
+    let mut clock = AuroraClock::default();
+    clock.tick(0.5);
+    assert_eq!(clock.elapsed(), 0.5);
+    clock.set_rate(4.0);
+    clock.tick(0.5);
+    assert_eq!(clock.elapsed(), 2.5);
+}
+
+#[test]
+fn paused_clock_only_moves_by_steps() {
+    let mut clock = AuroraClock::default();
+    clock.pause();
+    clock.tick(1.0);
+    assert_eq!(clock.elapsed(), 0.0);
+    clock.step(1.0 / 30.0);
+    assert_eq!(clock.elapsed(), 1.0 / 30.0);
+
+    clock.toggle_pause();
+    assert!(!clock.is_paused());
+    clock.tick(1.0);
+    assert_eq!(clock.elapsed(), 1.0 + 1.0 / 30.0);
+}
+
+#[test]
+fn negative_rate_runs_backwards() {
+    let mut clock = AuroraClock::default();
+    clock.set_elapsed(10.0);
+    clock.set_rate(-2.0);
+    clock.tick(1.5);
+    assert_eq!(clock.elapsed(), 7.0);
+}
+
+#[test]
+fn set_elapsed_is_read_back() {
+    let mut clock = AuroraClock::default();
+    for seconds in [0.0, 12.25, -3.5, 86_400.0 * 365.0 + 0.125] {
+        clock.set_elapsed(seconds);
+        assert_eq!(clock.elapsed(), seconds);
+    }
+}
+
+#[test]
+fn materials_follow_the_clock() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<Shader>()
+        .init_asset::<Image>()
+        .add_plugins(AuroraPlugin::default());
+
+    let settings = AuroraSettings {
+        speed: 1.7,
+        ..default()
+    };
+    let material = app
+        .world_mut()
+        .resource_mut::<Assets<CustomMaterial>>()
+        .add(CustomMaterial {
+            settings,
+            ..default()
+        });
+    {
+        let mut clock = app.world_mut().resource_mut::<AuroraClock>();
+        clock.pause();
+        clock.set_elapsed(4321.5);
+    }
+    app.update();
+
+    let phases = app
+        .world()
+        .resource::<Assets<CustomMaterial>>()
+        .get(&material)
+        .unwrap()
+        .phases;
+    assert_eq!(phases, AuroraPhases::new(4321.5, &settings));
+    assert_ne!(phases, AuroraPhases::default());
+}
@@ -0,0 +1,216 @@
+use aurora::{
+    AuroraMapping, AuroraPhases, AuroraSettings, NOISE_PERIOD,
+    cpu::{self, Noise},
+};
+use bevy::math::{Vec2, Vec3};
+
+#[test]
+fn hash_is_in_unit_range() {
+    let noise = Noise::new(0);
+    for x in -50..50 {
+        for y in -50..50 {
+            let h = noise.hash(Vec2::new(x as f32, y as f32));
+            assert!((0.0..1.0).contains(&h), "hash({x}, {y}) = {h}");
+        }
+    }
+}
+
+#[test]
+fn oklab_white_maps_to_white() {
+    let white = cpu::oklab_to_linear_srgb(Vec3::new(1.0, 0.0, 0.0));
+    assert!(white.abs_diff_eq(Vec3::ONE, 1e-4), "{white}");
+}
+
+#[test]
+fn fragment_is_deterministic() {
+    let settings = AuroraSettings::default();
+    let uv = Vec2::new(0.3, 0.55);
+    assert_eq!(
+        cpu::fragment(uv, 12.5, &settings),
+        cpu::fragment(uv, 12.5, &settings)
+    );
+}
+
+#[test]
+fn fragment_output_is_finite() {
+    let settings = AuroraSettings::default();
+    for i in 0..=16 {
+        for j in 0..=16 {
+            let uv = Vec2::new(i as f32, j as f32) / 16.0;
+            let color = cpu::fragment(uv, 37.0, &settings);
+            assert!(color.is_finite(), "{uv} -> {color}");
+        }
+    }
+}
+
+#[test]
+fn seeds_give_different_skies() {
+    let uv = Vec2::new(0.4, 0.5);
+    let a = cpu::fragment(uv, 3.0, &AuroraSettings::default());
+    let b = cpu::fragment(
+        uv,
+        3.0,
+        &AuroraSettings {
+            seed: 1,
+            ..Default::default()
+        },
+    );
+    assert_ne!(a, b);
+}
+
+#[test]
+fn hash_repeats_every_noise_period() {
+    let noise = Noise::new(3);
+    let period = NOISE_PERIOD as f32;
+    for (x, y) in [(0.0, 0.0), (17.0, -4.0), (-1.0, 255.0)] {
+        let p = Vec2::new(x, y);
+        assert_eq!(noise.hash(p), noise.hash(p + Vec2::new(period, -period)));
+    }
+}
+
+/// Largest change of any channel between two frames 1/60 s apart, starting at `time`.
+fn frame_to_frame_change(time: f64) -> f32 {
+    let settings = AuroraSettings::default();
+    let mut change: f32 = 0.0;
+    for i in 0..=16 {
+        for j in 0..=16 {
+            let uv = Vec2::new(i as f32, j as f32) / 16.0;
+            let a = cpu::fragment(uv, time, &settings);
+            let b = cpu::fragment(uv, time + 1.0 / 60.0, &settings);
+            change = change.max((a - b).abs().max_element());
+        }
+    }
+    change
+}
+
+#[test]
+fn animation_stays_smooth_after_a_month() {
+    let month = 30.0 * 24.0 * 3600.0;
+    for time in [10.0, 3600.0, month, month + 0.5] {
+        let change = frame_to_frame_change(time);
+        assert!(change < 0.05, "jump of {change} at t = {time}");
+    }
+}
+
+#[test]
+fn looping_animation_repeats_exactly() {
+    let settings = AuroraSettings {
+        loop_period: 8.0,
+        ..Default::default()
+    };
+    let fps = 30.0;
+    for frame in [0, 1, 97] {
+        let start = f64::from(frame) / fps;
+        let next_loop = f64::from(frame + 8 * 30) / fps;
+        assert_eq!(
+            AuroraPhases::new(start, &settings),
+            AuroraPhases::new(next_loop, &settings),
+            "frame {frame}"
+        );
+    }
+    let uv = Vec2::new(0.45, 0.5);
+    assert_ne!(
+        cpu::fragment(uv, 0.0, &settings),
+        cpu::fragment(uv, 4.0, &settings)
+    );
+}
+
+#[test]
+fn short_loops_still_move_every_term() {
+    let settings = AuroraSettings {
+        loop_period: 2.0,
+        ..Default::default()
+    };
+    let start = AuroraPhases::new(0.0, &settings);
+    let middle = AuroraPhases::new(1.0, &settings);
+    // The slowest oscillators turn once per loop instead of standing still
+    for (a, b) in [
+        (start.oscillators_a, middle.oscillators_a),
+        (start.oscillators_b, middle.oscillators_b),
+    ] {
+        for i in 0..4 {
+            assert_ne!(a[i], b[i], "oscillator {i} froze");
+        }
+    }
+    assert_ne!(start.oscillators_c.x, middle.oscillators_c.x);
+    assert_ne!(start.oscillators_c.y, middle.oscillators_c.y);
+    assert_eq!(start, AuroraPhases::new(2.0, &settings));
+}
+
+#[test]
+fn dome_mapping_has_no_seam() {
+    for elevation in [0.0, 0.3, 0.9] {
+        let start = cpu::sky_point(Vec2::new(0.0, elevation), AuroraMapping::Dome);
+        let end = cpu::sky_point(Vec2::new(1.0, elevation), AuroraMapping::Dome);
+        assert!(
+            start.coord.abs_diff_eq(end.coord, 1e-5),
+            "{start:?} {end:?}"
+        );
+        assert!((start.elevation - elevation).abs() < 1e-5);
+    }
+    // Every azimuth meets at the zenith
+    let a = cpu::sky_point(Vec2::new(0.1, 1.0), AuroraMapping::Dome);
+    let b = cpu::sky_point(Vec2::new(0.6, 1.0), AuroraMapping::Dome);
+    assert!(a.coord.abs_diff_eq(b.coord, 1e-5));
+}
+
+#[test]
+fn sky_is_ground_below_the_horizon() {
+    let settings = AuroraSettings::default();
+    let phases = AuroraPhases::new(12.0, &settings);
+    let down = cpu::sky(Vec3::NEG_Y, &phases, &settings);
+    assert_eq!(
+        down,
+        cpu::sky(Vec3::new(0.6, -0.8, 0.0), &phases, &settings)
+    );
+    for y in [0.0, 0.2, 0.7, 1.0] {
+        let direction = Vec3::new(0.3, y, -0.8).normalize();
+        let color = cpu::sky(direction, &phases, &settings);
+        assert!(color.is_finite() && color.min_element() >= 0.0);
+        assert_ne!(color, down, "sky at y = {y}");
+    }
+}
+
+#[test]
+fn spectral_lines_have_their_hue() {
+    let green = cpu::line_color(cpu::OXYGEN_GREEN_LINE);
+    let red = cpu::line_color(cpu::OXYGEN_RED_LINE);
+    let blue = cpu::line_color(cpu::NITROGEN_BLUE_LINE);
+    assert!(green.y > green.x && green.y > green.z, "{green}");
+    assert!(red.x > red.y && red.x > red.z, "{red}");
+    assert!(blue.z > blue.x && blue.z > blue.y, "{blue}");
+}
+
+#[test]
+fn physical_emission_is_green_below_red_above() {
+    let low = cpu::physical_emission(0.35);
+    let high = cpu::physical_emission(0.95);
+    assert!(low.y > low.x, "{low}");
+    assert!(high.x > high.y, "{high}");
+}
+
+#[test]
+fn physical_second_layer_is_red() {
+    let settings = AuroraSettings {
+        physical_emission: 1.0,
+        ..Default::default()
+    };
+    let without_second = AuroraSettings {
+        second_strength: 0.0,
+        ..settings
+    };
+    let phases = AuroraPhases::new(12.0, &settings);
+
+    // What the second layer adds along its middle, the main layer being the same in both
+    let mut added = Vec3::ZERO;
+    for x in 0..200 {
+        let point = cpu::SkyPoint {
+            coord: Vec2::new(x as f32 / 200.0, settings.second_height),
+            elevation: settings.second_height,
+        };
+        added += (cpu::aurora(point, &phases, &settings)
+            - cpu::aurora(point, &phases, &without_second))
+        .truncate();
+    }
+    assert!(added.x > added.y && added.x > added.z, "{added}");
+}
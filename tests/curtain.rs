@@ -0,0 +1,155 @@
+//! Ribbons spawned for `AuroraCurtains`, in an app running the plugin without rendering.
+
+use aurora::{AuroraCurtains, AuroraPlugin};
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, VertexAttributeValues},
+        primitives::Aabb,
+    },
+};
+
+/// An app running the main world systems of the plugin.
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<Shader>()
+    .init_asset::<Image>()
+    .add_plugins(AuroraPlugin::default());
+    app
+}
+
+fn ribbons(app: &App, curtains: Entity) -> Vec<Entity> {
+    app.world()
+        .get::<Children>(curtains)
+        .map(|children| children.to_vec())
+        .unwrap_or_default()
+}
+
+#[test]
+fn ribbons_are_sheets_facing_z() {
+    let mut app = app();
+    let curtains = app
+        .world_mut()
+        .spawn(AuroraCurtains {
+            count: 3,
+            segments: 8,
+            ..default()
+        })
+        .id();
+    app.update();
+
+    let ribbons = ribbons(&app, curtains);
+    assert_eq!(ribbons.len(), 3);
+    let handle = &app.world().get::<Mesh3d>(ribbons[0]).unwrap().0;
+    let mesh = app.world().resource::<Assets<Mesh>>().get(handle).unwrap();
+
+    let VertexAttributeValues::Float32x3(positions) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap()
+    else {
+        panic!("positions are not vec3");
+    };
+    let VertexAttributeValues::Float32x2(uvs) = mesh.attribute(Mesh::ATTRIBUTE_UV_0).unwrap()
+    else {
+        panic!("uvs are not vec2");
+    };
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        panic!("indices are not u32");
+    };
+    assert_eq!(positions.len(), 2 * 9);
+    assert_eq!(indices.len(), 6 * 8);
+
+    // Every triangle faces +Z, counter-clockwise
+    for triangle in indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i] as usize]));
+        assert!((b - a).cross(c - a).z > 0.0, "{triangle:?}");
+    }
+    for uv in uvs {
+        assert!(uv.iter().all(|c| (0.0..=1.0).contains(c)), "{uv:?}");
+    }
+    assert!(uvs.contains(&[0.0, 0.0]) && uvs.contains(&[1.0, 1.0]));
+
+    // The bounds leave room for the folds on both sides of the flat sheet
+    let aabb = app.world().get::<Aabb>(ribbons[0]).unwrap();
+    assert!(aabb.half_extents.z > 0.0);
+}
+
+#[test]
+fn changing_the_layout_respawns_the_ribbons() {
+    let mut app = app();
+    let curtains = app.world_mut().spawn(AuroraCurtains::default()).id();
+    app.update();
+    let first = ribbons(&app, curtains);
+    assert_eq!(first.len(), 5);
+
+    // Folds only update the material and the bounds
+    let mut entity = app.world_mut().entity_mut(curtains);
+    entity.get_mut::<AuroraCurtains>().unwrap().fold_amplitude = 30.0;
+    let folded = app.world().get::<Aabb>(first[0]).unwrap().half_extents.z;
+    app.update();
+    assert_eq!(ribbons(&app, curtains), first);
+    assert!(app.world().get::<Aabb>(first[0]).unwrap().half_extents.z > folded);
+
+    let mut entity = app.world_mut().entity_mut(curtains);
+    entity.get_mut::<AuroraCurtains>().unwrap().count = 2;
+    app.update();
+    let second = ribbons(&app, curtains);
+    assert_eq!(second.len(), 2);
+    for ribbon in first {
+        assert!(app.world().get_entity(ribbon).is_err(), "{ribbon} was kept");
+    }
+}
+
+#[test]
+fn removing_the_curtains_despawns_the_ribbons() {
+    let mut app = app();
+    let removed = app.world_mut().spawn(AuroraCurtains::default()).id();
+    let despawned = app.world_mut().spawn(AuroraCurtains::default()).id();
+    app.update();
+    let ribbons = [ribbons(&app, removed), ribbons(&app, despawned)].concat();
+    assert_eq!(ribbons.len(), 10);
+
+    app.world_mut()
+        .entity_mut(removed)
+        .remove::<AuroraCurtains>();
+    // Leaves the children behind, unlike `despawn_recursive`
+    app.world_mut().despawn(despawned);
+    app.update();
+
+    for ribbon in ribbons {
+        assert!(app.world().get_entity(ribbon).is_err(), "{ribbon} was kept");
+    }
+    assert!(app.world().get_entity(removed).is_ok());
+}
+
+#[test]
+fn bounds_are_in_the_ribbon_space_whatever_the_parent_transform() {
+    let mut app = app();
+    let curtains = AuroraCurtains {
+        count: 1,
+        ..default()
+    };
+    let plain = app.world_mut().spawn(curtains.clone()).id();
+    // Squashed along the fold axis and turned, the folds shrink and turn with the ribbons
+    let scaled = app
+        .world_mut()
+        .spawn((
+            curtains.clone(),
+            Transform::from_scale(Vec3::new(3.0, 1.0, 0.2))
+                .with_rotation(Quat::from_rotation_y(0.7)),
+        ))
+        .id();
+    app.update();
+
+    let aabb = |entity| *app.world().get::<Aabb>(ribbons(&app, entity)[0]).unwrap();
+    assert_eq!(aabb(scaled), aabb(plain));
+    // The folds stray up to half of `waviness` plus a tenth, 1.3 times that at the top
+    let fold = (0.5 * curtains.settings.waviness + 0.1) * curtains.fold_amplitude * 1.3;
+    assert!(aabb(scaled).half_extents.z >= fold - 1e-4);
+}
@@ -0,0 +1,51 @@
+use aurora::SkyDome;
+use bevy::{
+    math::Vec3,
+    render::mesh::{Indices, Mesh, MeshBuilder, VertexAttributeValues},
+};
+
+#[test]
+fn dome_faces_its_center() {
+    let mesh = SkyDome::new(10.0).build();
+    let VertexAttributeValues::Float32x3(positions) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap()
+    else {
+        panic!("positions are not vec3");
+    };
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        panic!("indices are not u32");
+    };
+
+    for triangle in indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i] as usize]));
+        let normal = (b - a).cross(c - a);
+        if normal.length() < 1e-6 {
+            continue;
+        }
+        // Counter-clockwise from the center means the normal points back at it
+        assert!(normal.dot((a + b + c) / 3.0) < 0.0, "{triangle:?}");
+    }
+    for position in positions {
+        let position = Vec3::from(*position);
+        assert!((position.length() - 10.0).abs() < 1e-4);
+        assert!(position.y >= -1e-6);
+    }
+}
+
+#[test]
+fn dome_uvs_are_azimuth_and_elevation() {
+    let mesh = SkyDome::new(1.0).build();
+    let VertexAttributeValues::Float32x3(positions) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap()
+    else {
+        panic!("positions are not vec3");
+    };
+    let VertexAttributeValues::Float32x2(uvs) = mesh.attribute(Mesh::ATTRIBUTE_UV_0).unwrap()
+    else {
+        panic!("uvs are not vec2");
+    };
+    for (position, uv) in positions.iter().zip(uvs) {
+        let elevation = position[1].asin() / std::f32::consts::FRAC_PI_2;
+        assert!((elevation - uv[1]).abs() < 1e-4, "{position:?} {uv:?}");
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use aurora::{
+    AuroraSettings,
+    export::{ExportFormat, FrameWriter, MIN_FPS, frame_path, linear_to_rec709_ycbcr},
+    render::{self, Frame},
+};
+use bevy::math::Vec3;
+use image::AnimationDecoder;
+
+fn out_path(name: &str) -> String {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn frames(count: u32) -> Vec<Frame> {
+    let settings = AuroraSettings::default();
+    (0..count)
+        .map(|i| render::render_frame(&settings, f64::from(i) * 5.0, 16, 8))
+        .collect()
+}
+
+fn export(path: &str, frames: &[Frame]) {
+    export_at(path, frames, 24.0);
+}
+
+fn export_at(path: &str, frames: &[Frame], fps: f64) {
+    let mut writer = FrameWriter::create(path, 16, 8, frames.len() as u32, fps).unwrap();
+    for frame in frames {
+        writer.write(frame).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+#[test]
+fn format_follows_the_path() {
+    assert_eq!(
+        ExportFormat::from_path("out/aurora_###.png").unwrap(),
+        ExportFormat::PngSequence
+    );
+    assert_eq!(
+        ExportFormat::from_path("loop.GIF").unwrap(),
+        ExportFormat::Gif
+    );
+    assert_eq!(
+        ExportFormat::from_path("loop.apng").unwrap(),
+        ExportFormat::Apng
+    );
+    assert!(ExportFormat::from_path("loop.mp4").is_err());
+    assert!(ExportFormat::from_path("out_###.gif").is_err());
+    assert!(ExportFormat::from_path("clip#1.y4m").is_err());
+    assert!(ExportFormat::from_path("frames_##").is_err());
+    assert_eq!(frame_path("a_###.png", 7), "a_007.png");
+}
+
+#[test]
+fn apng_holds_every_frame() {
+    let path = out_path("export_test.png");
+    export(&path, &frames(3));
+
+    let decoder = png::Decoder::new(BufReader::new(File::open(&path).unwrap()));
+    let reader = decoder.read_info().unwrap();
+    let animation = reader.info().animation_control.unwrap();
+    assert_eq!(animation.num_frames, 3);
+    assert_eq!(animation.num_plays, 0);
+}
+
+#[test]
+fn gif_holds_every_frame() {
+    let path = out_path("export_test.gif");
+    export(&path, &frames(3));
+
+    let decoder =
+        image::codecs::gif::GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    let decoded = decoder.into_frames().collect_frames().unwrap();
+    assert_eq!(decoded.len(), 3);
+    assert_eq!(decoded[0].buffer().dimensions(), (16, 8));
+}
+
+#[test]
+fn frames_of_another_size_are_rejected() {
+    let path = out_path("export_test_size.png");
+    let mut writer = FrameWriter::create(&path, 32, 8, 1, 24.0).unwrap();
+    assert!(writer.write(&frames(1)[0]).is_err());
+}
+
+#[test]
+fn frame_rates_that_cannot_be_stored_are_rejected() {
+    let path = out_path("export_test_fps.gif");
+    for fps in [0.0, -24.0, 1e-6, MIN_FPS * 0.99, f64::NAN, f64::INFINITY] {
+        assert!(FrameWriter::create(&path, 16, 8, 2, fps).is_err(), "{fps}");
+    }
+}
+
+#[test]
+fn slowest_frame_rate_is_stored_exactly() {
+    let path = out_path("export_test_slowest.png");
+    export_at(&path, &frames(2), MIN_FPS);
+    let decoder = png::Decoder::new(BufReader::new(File::open(&path).unwrap()));
+    let reader = decoder.read_info().unwrap();
+    let control = reader.info().frame_control.unwrap();
+    assert_eq!((control.delay_num, control.delay_den), (65535, 100));
+
+    let path = out_path("export_test_slowest.gif");
+    export_at(&path, &frames(2), MIN_FPS);
+    let decoder =
+        image::codecs::gif::GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    for frame in decoder.into_frames().collect_frames().unwrap() {
+        assert_eq!(frame.delay().numer_denom_ms(), (655_350, 1));
+    }
+}
+
+#[test]
+fn y4m_has_a_header_and_three_planes_per_frame() {
+    let path = out_path("export_test.y4m");
+    export(&path, &frames(2));
+
+    let bytes = std::fs::read(&path).unwrap();
+    let header = b"YUV4MPEG2 W16 H8 F24:1 Ip A1:1 C444 XCOLORRANGE=LIMITED\n";
+    assert!(bytes.starts_with(header));
+    let frame_len = b"FRAME\n".len() + 3 * 16 * 8;
+    assert_eq!(bytes.len(), header.len() + 2 * frame_len);
+    assert_eq!(&bytes[header.len()..header.len() + 6], b"FRAME\n");
+}
+
+#[test]
+fn rec709_uses_limited_range() {
+    assert_eq!(linear_to_rec709_ycbcr(Vec3::ZERO), [16, 128, 128]);
+    assert_eq!(linear_to_rec709_ycbcr(Vec3::ONE), [235, 128, 128]);
+    assert_eq!(linear_to_rec709_ycbcr(Vec3::splat(4.0)), [235, 128, 128]);
+    let [_, cb, cr] = linear_to_rec709_ycbcr(Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!((cb, cr), (102, 240));
+}
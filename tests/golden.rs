@@ -0,0 +1,160 @@
+//! Golden image regression tests for the aurora look.
+//!
+//! Each case is rendered with the CPU reference implementation and compared to the PNG
+//! checked in under `tests/golden/`, pixel by pixel in OKLab. On failure the rendered image
+//! and a difference heatmap are written to `target/golden-diff/`.
+//!
+//! After an intentional change of the look, regenerate the references with
+//! `AURORA_BLESS=1 cargo test --test golden`.
+
+use std::path::{Path, PathBuf};
+
+use aurora::{AuroraPreset, AuroraSettings, render};
+use bevy::color::{ColorToComponents, LinearRgba, Oklaba, Srgba};
+use image::RgbImage;
+
+/// Largest OKLab distance allowed for a single pixel, about a just noticeable difference.
+const MAX_DELTA_E: f32 = 0.02;
+/// Largest OKLab distance allowed on average over the image.
+const MAX_MEAN_DELTA_E: f32 = 0.002;
+
+struct Case {
+    name: &'static str,
+    preset: &'static str,
+    time: f64,
+    width: u32,
+    height: u32,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "default_start",
+        preset: "default",
+        time: 0.0,
+        width: 96,
+        height: 54,
+    },
+    Case {
+        name: "default_late",
+        preset: "default",
+        time: 137.25,
+        width: 96,
+        height: 54,
+    },
+    Case {
+        name: "default_square",
+        preset: "default",
+        time: 12.5,
+        width: 64,
+        height: 64,
+    },
+    Case {
+        name: "crimson_storm",
+        preset: "crimson_storm",
+        time: 42.0,
+        width: 96,
+        height: 54,
+    },
+    Case {
+        name: "physical",
+        preset: "physical",
+        time: 42.0,
+        width: 96,
+        height: 54,
+    },
+];
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn load_preset(name: &str) -> AuroraSettings {
+    let path = manifest_dir().join(format!("assets/presets/{name}.aurora.ron"));
+    let bytes = std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    AuroraPreset::from_ron(&bytes).unwrap().settings
+}
+
+fn to_oklab(pixel: &image::Rgb<u8>) -> Oklaba {
+    let [r, g, b] = pixel.0;
+    Oklaba::from(LinearRgba::from(Srgba::rgb_u8(r, g, b)))
+}
+
+fn delta_e(a: Oklaba, b: Oklaba) -> f32 {
+    (a.to_vec3() - b.to_vec3()).length()
+}
+
+/// Grayscale map of the per-pixel difference, white at [`MAX_DELTA_E`] and above.
+fn diff_image(actual: &RgbImage, expected: &RgbImage) -> RgbImage {
+    RgbImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let d = delta_e(
+            to_oklab(actual.get_pixel(x, y)),
+            to_oklab(expected.get_pixel(x, y)),
+        );
+        let v = ((d / MAX_DELTA_E).min(1.0) * 255.0) as u8;
+        image::Rgb([v, v, v])
+    })
+}
+
+fn save(image: &RgbImage, path: &Path) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    image.save(path).unwrap();
+}
+
+/// Renders `case` and returns a description of the mismatch, if any.
+fn check(case: &Case, bless: bool) -> Option<String> {
+    let settings = load_preset(case.preset);
+    let actual = render::render_frame(&settings, case.time, case.width, case.height).to_rgb8();
+    let reference = manifest_dir().join(format!("tests/golden/{}.png", case.name));
+
+    if bless {
+        save(&actual, &reference);
+        return None;
+    }
+
+    let expected = match image::open(&reference) {
+        Ok(image) => image.to_rgb8(),
+        Err(e) => return Some(format!("{}: {e}", reference.display())),
+    };
+    if expected.dimensions() != actual.dimensions() {
+        return Some(format!(
+            "{}: expected a {:?} image, rendered {:?}",
+            case.name,
+            expected.dimensions(),
+            actual.dimensions()
+        ));
+    }
+
+    let deltas: Vec<f32> = actual
+        .pixels()
+        .zip(expected.pixels())
+        .map(|(a, e)| delta_e(to_oklab(a), to_oklab(e)))
+        .collect();
+    let max = deltas.iter().copied().fold(0.0, f32::max);
+    let mean = deltas.iter().sum::<f32>() / deltas.len() as f32;
+    if max <= MAX_DELTA_E && mean <= MAX_MEAN_DELTA_E {
+        return None;
+    }
+
+    let out_dir = manifest_dir().join("target/golden-diff");
+    save(&actual, &out_dir.join(format!("{}.actual.png", case.name)));
+    save(
+        &diff_image(&actual, &expected),
+        &out_dir.join(format!("{}.diff.png", case.name)),
+    );
+    Some(format!(
+        "{}: max delta E {max:.4} (limit {MAX_DELTA_E}), mean {mean:.5} (limit {MAX_MEAN_DELTA_E}), see {}",
+        case.name,
+        out_dir.display()
+    ))
+}
+
+#[test]
+fn golden_images() {
+    let bless = std::env::var_os("AURORA_BLESS").is_some();
+    let failures: Vec<String> = CASES.iter().filter_map(|case| check(case, bless)).collect();
+    assert!(
+        failures.is_empty(),
+        "golden images differ:\n{}",
+        failures.join("\n")
+    );
+}
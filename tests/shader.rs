@@ -0,0 +1,546 @@
+//! Validation of the aurora shaders and of the bindings of every material against its shader.
+//!
+//! The Bevy imports are replaced by stubs declaring what the shaders use from them.
+
+use aurora::{
+    AuroraMappingSettings, AuroraPhases, AuroraSettings, CurtainFolds, CurtainMaterial,
+    CustomMaterial, CustomMaterial2d, CustomUiMaterial,
+};
+use bevy::{
+    math::{Vec2, Vec3, Vec4},
+    reflect::Struct,
+    render::{
+        render_resource::{
+            AsBindGroup, BindingType, BufferBindingType, ShaderType,
+            encase::{UniformBuffer, internal::WriteInto},
+        },
+        renderer::RenderDevice,
+    },
+    tasks::block_on,
+};
+use naga::{AddressSpace, Module, ResourceBinding, ScalarKind, TypeInner};
+use naga_oil::compose::{
+    ComposableModuleDescriptor, Composer, NagaModuleDescriptor, ShaderDefValue, ShaderLanguage,
+    ShaderType as ComposerShaderType,
+};
+
+const AURORA_SHADER: &str = include_str!("../assets/shaders/animate_shader.wgsl");
+const SKYBOX_SHADER: &str = include_str!("../assets/shaders/aurora_skybox.wgsl");
+const ENVIRONMENT_SHADER: &str = include_str!("../assets/shaders/aurora_environment.wgsl");
+const SHADER_2D: &str = include_str!("../assets/shaders/aurora_2d.wgsl");
+const UI_SHADER: &str = include_str!("../assets/shaders/aurora_ui.wgsl");
+const CURTAIN_SHADER: &str = include_str!("../assets/shaders/aurora_curtain.wgsl");
+const BACKGROUND_SHADER: &str = include_str!("../assets/shaders/aurora_background.wgsl");
+
+/// Bind group Bevy uses for the bindings of 3D and 2D materials.
+const MATERIAL_GROUP: u32 = 2;
+/// Bind group Bevy uses for the bindings of UI materials.
+const UI_MATERIAL_GROUP: u32 = 1;
+
+const STUBS: &[(&str, &str)] = &[
+    (
+        "stubs/view.wgsl",
+        "
+#define_import_path bevy_render::view
+
+struct View {
+    clip_from_world: mat4x4<f32>,
+    world_from_clip: mat4x4<f32>,
+    world_position: vec3<f32>,
+    viewport: vec4<f32>,
+}
+",
+    ),
+    (
+        "stubs/mesh_view_bindings.wgsl",
+        "
+#define_import_path bevy_pbr::mesh_view_bindings
+
+#import bevy_render::view::View
+
+@group(0) @binding(0) var<uniform> view: View;
+
+struct Globals {
+    time: f32,
+    delta_time: f32,
+    frame_count: u32,
+}
+
+@group(0) @binding(11) var<uniform> globals: Globals;
+",
+    ),
+    (
+        "stubs/forward_io.wgsl",
+        "
+#define_import_path bevy_pbr::forward_io
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    @location(6) @interpolate(flat) instance_index: u32,
+#endif
+}
+",
+    ),
+    (
+        "stubs/mesh_functions.wgsl",
+        "
+#define_import_path bevy_pbr::mesh_functions
+
+fn get_world_from_local(instance_index: u32) -> mat4x4<f32> {
+    return mat4x4<f32>(
+        vec4<f32>(1.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 1.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 1.0, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+}
+",
+    ),
+    (
+        "stubs/mesh2d_vertex_output.wgsl",
+        "
+#define_import_path bevy_sprite::mesh2d_vertex_output
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+}
+",
+    ),
+    (
+        "stubs/ui_vertex_output.wgsl",
+        "
+#define_import_path bevy_ui::ui_vertex_output
+
+struct UiVertexOutput {
+    @location(0) uv: vec2<f32>,
+    @location(1) border_widths: vec4<f32>,
+    @location(2) @interpolate(flat) size: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+",
+    ),
+    (
+        "stubs/fullscreen_vertex_shader.wgsl",
+        "
+#define_import_path bevy_core_pipeline::fullscreen_vertex_shader
+
+struct FullscreenVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+",
+    ),
+];
+
+/// Modules of this crate imported by the shaders, after the stubs they may import.
+const MODULES: &[(&str, &str)] = &[(
+    "aurora_core.wgsl",
+    include_str!("../assets/shaders/aurora_core.wgsl"),
+)];
+
+fn compose(path: &str, source: &str, shader_defs: &[&str]) -> Module {
+    let mut composer = Composer::default();
+    for (stub_path, stub) in STUBS.iter().chain(MODULES) {
+        let result = composer.add_composable_module(ComposableModuleDescriptor {
+            source: stub,
+            file_path: stub_path,
+            language: ShaderLanguage::Wgsl,
+            as_name: None,
+            additional_imports: &[],
+            shader_defs: Default::default(),
+        });
+        if let Err(e) = result {
+            panic!("{}", e.emit_to_string(&composer));
+        }
+    }
+    let result = composer.make_naga_module(NagaModuleDescriptor {
+        source,
+        file_path: path,
+        shader_type: ComposerShaderType::Wgsl,
+        shader_defs: shader_defs
+            .iter()
+            .map(|def| (def.to_string(), ShaderDefValue::Bool(true)))
+            .collect(),
+        ..Default::default()
+    });
+    result.unwrap_or_else(|e| panic!("{}", e.emit_to_string(&composer)))
+}
+
+fn aurora_module() -> Module {
+    aurora_module_with(&[])
+}
+
+/// The shader as specialized with `shader_defs`, validated.
+fn aurora_module_with(shader_defs: &[&str]) -> Module {
+    validated("animate_shader.wgsl", AURORA_SHADER, shader_defs)
+}
+
+fn validated(path: &str, source: &str, shader_defs: &[&str]) -> Module {
+    let module = compose(path, source, shader_defs);
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .unwrap_or_else(|e| panic!("{}", e.emit_to_string(source)));
+    module
+}
+
+/// Resources the shader declares in the material bind group `group`, as `(binding, global)`.
+fn material_bindings(module: &Module, group: u32) -> Vec<(u32, &naga::GlobalVariable)> {
+    let mut bindings: Vec<_> = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, global)| match global.binding {
+            Some(ResourceBinding {
+                group: global_group,
+                binding,
+            }) if global_group == group => Some((binding, global)),
+            _ => None,
+        })
+        .collect();
+    bindings.sort_by_key(|(binding, _)| *binding);
+    bindings
+}
+
+#[test]
+fn aurora_shader_validates() {
+    let module = aurora_module();
+    assert!(
+        module.entry_points.iter().any(|e| e.name == "fragment"),
+        "no `fragment` entry point"
+    );
+}
+
+#[test]
+fn mappings_validate() {
+    for mapping in [
+        "AURORA_MAPPING_DOME",
+        "AURORA_MAPPING_WORLD_XZ",
+        "AURORA_MAPPING_WORLD_PROJECTED",
+        "AURORA_MAPPING_SCREEN",
+        "AURORA_MAPPING_VOLUMETRIC",
+    ] {
+        aurora_module_with(&[mapping]);
+    }
+}
+
+#[test]
+fn triplanar_mapping_validates() {
+    aurora_module_with(&["AURORA_MAPPING_TRIPLANAR", "VERTEX_OUTPUT_INSTANCE_INDEX"]);
+}
+
+#[test]
+fn skybox_shader_validates() {
+    let module = validated("aurora_skybox.wgsl", SKYBOX_SHADER, &[]);
+    let bake = module
+        .entry_points
+        .iter()
+        .find(|e| e.name == "bake")
+        .expect("no `bake` entry point");
+    assert_eq!(bake.stage, naga::ShaderStage::Compute);
+    assert_eq!(bake.workgroup_size, [8, 8, 1]);
+}
+
+#[test]
+fn environment_shader_validates() {
+    let module = validated("aurora_environment.wgsl", ENVIRONMENT_SHADER, &[]);
+    for name in ["filter_specular", "filter_diffuse"] {
+        let entry_point = module
+            .entry_points
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap_or_else(|| panic!("no `{name}` entry point"));
+        assert_eq!(entry_point.stage, naga::ShaderStage::Compute);
+        assert_eq!(entry_point.workgroup_size, [8, 8, 1]);
+    }
+}
+
+#[test]
+fn material_2d_shader_validates() {
+    let module = validated("aurora_2d.wgsl", SHADER_2D, &[]);
+    assert!(
+        module.entry_points.iter().any(|e| e.name == "fragment"),
+        "no `fragment` entry point"
+    );
+}
+
+#[test]
+fn ui_shader_validates() {
+    let module = validated("aurora_ui.wgsl", UI_SHADER, &[]);
+    assert!(
+        module.entry_points.iter().any(|e| e.name == "fragment"),
+        "no `fragment` entry point"
+    );
+}
+
+#[test]
+fn curtain_shader_validates() {
+    let module = validated("aurora_curtain.wgsl", CURTAIN_SHADER, &[]);
+    for (name, stage) in [
+        ("vertex", naga::ShaderStage::Vertex),
+        ("fragment", naga::ShaderStage::Fragment),
+    ] {
+        let entry_point = module
+            .entry_points
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap_or_else(|| panic!("no `{name}` entry point"));
+        assert_eq!(entry_point.stage, stage);
+    }
+}
+
+#[test]
+fn background_shader_validates() {
+    let module = validated("aurora_background.wgsl", BACKGROUND_SHADER, &[]);
+    assert!(
+        module.entry_points.iter().any(|e| e.name == "fragment"),
+        "no `fragment` entry point"
+    );
+}
+
+/// Gives every field a distinct value, so misplaced fields cannot go unnoticed.
+fn distinct_fields<T: Struct + Default>() -> T {
+    let mut value = T::default();
+    let mut next = 1.0;
+    let mut take = || {
+        next += 1.0;
+        next
+    };
+    for i in 0..value.field_len() {
+        let field = value.field_at_mut(i).unwrap();
+        if let Some(v) = field.try_downcast_mut::<f32>() {
+            *v = take();
+        } else if let Some(v) = field.try_downcast_mut::<u32>() {
+            *v = take() as u32;
+        } else if let Some(v) = field.try_downcast_mut::<Vec2>() {
+            *v = Vec2::new(take(), take());
+        } else if let Some(v) = field.try_downcast_mut::<Vec3>() {
+            *v = Vec3::new(take(), take(), take());
+        } else if let Some(v) = field.try_downcast_mut::<Vec4>() {
+            *v = Vec4::new(take(), take(), take(), take());
+        } else {
+            panic!("unsupported type for field `{}`", value.name_at(i).unwrap());
+        }
+    }
+    value
+}
+
+/// Components of a reflected struct field, as raw 32-bit values.
+fn field_bits(value: &dyn Struct, name: &str) -> Vec<u32> {
+    let field = value
+        .field(name)
+        .unwrap_or_else(|| panic!("no field `{name}` on the Rust side"));
+    if let Some(v) = field.try_downcast_ref::<f32>() {
+        vec![v.to_bits()]
+    } else if let Some(v) = field.try_downcast_ref::<u32>() {
+        vec![*v]
+    } else if let Some(v) = field.try_downcast_ref::<Vec2>() {
+        v.to_array().map(f32::to_bits).to_vec()
+    } else if let Some(v) = field.try_downcast_ref::<Vec3>() {
+        v.to_array().map(f32::to_bits).to_vec()
+    } else if let Some(v) = field.try_downcast_ref::<Vec4>() {
+        v.to_array().map(f32::to_bits).to_vec()
+    } else {
+        panic!("unsupported type for field `{name}`")
+    }
+}
+
+/// Checks that `T`, as uploaded by encase, matches the uniform struct of `module` at `binding`
+/// of the material group `group` field by field.
+fn assert_uniform_layout<T>(module: &Module, group: u32, binding: u32)
+where
+    T: Struct + Default + ShaderType + WriteInto,
+{
+    let type_name = std::any::type_name::<T>();
+    let bindings = material_bindings(module, group);
+    let Some((_, global)) = bindings.iter().find(|(b, _)| *b == binding) else {
+        panic!("no uniform at binding {binding} of the material group {group}");
+    };
+    assert_eq!(global.space, AddressSpace::Uniform);
+
+    let TypeInner::Struct { members, span } = &module.types[global.ty].inner else {
+        panic!("the uniform at binding {binding} is not a struct");
+    };
+    assert_eq!(
+        u64::from(*span),
+        T::min_size().get(),
+        "uniform size differs between the shader and `{type_name}`"
+    );
+
+    let value = distinct_fields::<T>();
+    let rust_names: Vec<_> = (0..value.field_len())
+        .map(|i| value.name_at(i).unwrap())
+        .collect();
+    let shader_names: Vec<_> = members.iter().map(|m| m.name.as_deref().unwrap()).collect();
+    assert_eq!(
+        rust_names, shader_names,
+        "fields of `{type_name}` differ or are in another order"
+    );
+
+    let mut buffer = UniformBuffer::new(Vec::<u8>::new());
+    buffer.write(&value).unwrap();
+    let bytes = buffer.into_inner();
+
+    for member in members {
+        let name = member.name.as_deref().unwrap();
+        let (count, kind) = match module.types[member.ty].inner {
+            TypeInner::Scalar(scalar) => (1, scalar.kind),
+            TypeInner::Vector { size, scalar } => (size as usize, scalar.kind),
+            ref other => panic!("unsupported type for member `{name}`: {other:?}"),
+        };
+        let expected = field_bits(&value, name);
+        assert_eq!(expected.len(), count, "component count of `{name}`");
+        let actual: Vec<u32> = (0..count)
+            .map(|i| {
+                let at = member.offset as usize + 4 * i;
+                u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            actual, expected,
+            "value of `{name}` (as {kind:?}) at offset {}",
+            member.offset
+        );
+        assert!(
+            matches!(kind, ScalarKind::Float | ScalarKind::Uint),
+            "unexpected scalar kind for `{name}`"
+        );
+    }
+}
+
+/// Checks that the shader declares nothing but `count` bindings in the material group.
+fn assert_material_bindings(module: &Module, group: u32, count: u32) {
+    let bindings: Vec<_> = material_bindings(module, group)
+        .iter()
+        .map(|(b, _)| *b)
+        .collect();
+    assert_eq!(
+        bindings,
+        (0..count).collect::<Vec<_>>(),
+        "the shader has other material bindings"
+    );
+}
+
+#[test]
+fn settings_layout_matches_shader() {
+    assert_uniform_layout::<AuroraSettings>(&aurora_module(), MATERIAL_GROUP, 0);
+}
+
+#[test]
+fn phases_layout_matches_shader() {
+    assert_uniform_layout::<AuroraPhases>(&aurora_module(), MATERIAL_GROUP, 1);
+}
+
+#[test]
+fn mapping_settings_layout_matches_shader() {
+    assert_uniform_layout::<AuroraMappingSettings>(&aurora_module(), MATERIAL_GROUP, 2);
+}
+
+#[test]
+fn material_bindings_are_the_uniforms() {
+    assert_material_bindings(&aurora_module(), MATERIAL_GROUP, 3);
+}
+
+#[test]
+fn material_2d_layouts_match_shader() {
+    let module = validated("aurora_2d.wgsl", SHADER_2D, &[]);
+    assert_uniform_layout::<AuroraSettings>(&module, MATERIAL_GROUP, 0);
+    assert_uniform_layout::<AuroraPhases>(&module, MATERIAL_GROUP, 1);
+    assert_material_bindings(&module, MATERIAL_GROUP, 2);
+}
+
+#[test]
+fn curtain_material_layouts_match_shader() {
+    let module = validated("aurora_curtain.wgsl", CURTAIN_SHADER, &[]);
+    assert_uniform_layout::<AuroraSettings>(&module, MATERIAL_GROUP, 0);
+    assert_uniform_layout::<AuroraPhases>(&module, MATERIAL_GROUP, 1);
+    assert_uniform_layout::<CurtainFolds>(&module, MATERIAL_GROUP, 2);
+    assert_material_bindings(&module, MATERIAL_GROUP, 3);
+}
+
+#[test]
+fn ui_material_layouts_match_shader() {
+    let module = validated("aurora_ui.wgsl", UI_SHADER, &[]);
+    assert_uniform_layout::<AuroraSettings>(&module, UI_MATERIAL_GROUP, 0);
+    assert_uniform_layout::<AuroraPhases>(&module, UI_MATERIAL_GROUP, 1);
+    assert_material_bindings(&module, UI_MATERIAL_GROUP, 2);
+}
+
+/// A device to build bind group layouts with.
+fn render_device() -> RenderDevice {
+    let instance = wgpu::Instance::default();
+    let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        .expect("no graphics adapter");
+    let (device, _queue) = block_on(adapter.request_device(&Default::default(), None))
+        .expect("could not create a device");
+    device.into()
+}
+
+/// Checks the bind group layout of `M`, as built on a device, against the material group
+/// `group` of `module`.
+fn assert_bind_group_layout<M: AsBindGroup>(module: &Module, group: u32) {
+    let render_device = render_device();
+    let bindings = material_bindings(module, group);
+    let entries = M::bind_group_layout_entries(&render_device);
+
+    assert_eq!(
+        entries.iter().map(|e| e.binding).collect::<Vec<_>>(),
+        bindings.iter().map(|(b, _)| *b).collect::<Vec<_>>(),
+        "bindings of the material and of the shader differ"
+    );
+    for (entry, (binding, global)) in entries.iter().zip(&bindings) {
+        let BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            min_binding_size,
+            ..
+        } = entry.ty
+        else {
+            panic!("binding {binding} is not a uniform buffer in the material");
+        };
+        assert_eq!(global.space, AddressSpace::Uniform, "binding {binding}");
+        let size = module.types[global.ty].inner.size(module.to_ctx());
+        assert_eq!(
+            min_binding_size.map(|s| s.get()),
+            Some(u64::from(size)),
+            "size of binding {binding}"
+        );
+    }
+}
+
+// The tests above check the same bindings without a device. Run these with
+// `cargo test -- --ignored` on a machine with a graphics adapter.
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn bind_group_layout_matches_shader() {
+    assert_bind_group_layout::<CustomMaterial>(&aurora_module(), MATERIAL_GROUP);
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn bind_group_layout_2d_matches_shader() {
+    let module = validated("aurora_2d.wgsl", SHADER_2D, &[]);
+    assert_bind_group_layout::<CustomMaterial2d>(&module, MATERIAL_GROUP);
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn bind_group_layout_ui_matches_shader() {
+    let module = validated("aurora_ui.wgsl", UI_SHADER, &[]);
+    assert_bind_group_layout::<CustomUiMaterial>(&module, UI_MATERIAL_GROUP);
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn bind_group_layout_curtain_matches_shader() {
+    let module = validated("aurora_curtain.wgsl", CURTAIN_SHADER, &[]);
+    assert_bind_group_layout::<CurtainMaterial>(&module, MATERIAL_GROUP);
+}